@@ -0,0 +1,39 @@
+//! Fingerprints the models the colliders are baked from, so colliders baked from older models
+//! aren't used with newer ones.
+//!
+//! The baked colliders aren't kept in the repository, only written by `just bake-colliders` as
+//! part of packaging the game, so until then a manifest saying nothing was baked stands in for
+//! them.
+
+use std::{env, fs, path::Path};
+
+/// The models the baked colliders are built from, relative to the crate.
+const MODELS: [&str; 2] = ["assets/scenes/board.glb", "assets/scenes/stone.glb"];
+
+/// Where the game looks for the baked colliders' manifest, relative to the crate.
+const MANIFEST: &str = "assets/colliders/baked.colliders.ron";
+
+fn main() {
+    let root = env::var("CARGO_MANIFEST_DIR").unwrap();
+    // FNV-1a, which is simple enough to stay the same whichever toolchain builds the game.
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for model in MODELS {
+        println!("cargo:rerun-if-changed={model}");
+        let bytes = fs::read(Path::new(&root).join(model))
+            .unwrap_or_else(|e| panic!("Unable to read {model}: {e}"));
+        for byte in bytes {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+    println!("cargo:rustc-env=MODELS_HASH={hash:016x}");
+
+    // The game won't load without a manifest, so say the colliders were never baked.
+    let manifest = Path::new(&root).join(MANIFEST);
+    if !manifest.exists() {
+        fs::create_dir_all(manifest.parent().unwrap())
+            .unwrap_or_else(|e| panic!("Unable to write {MANIFEST}: {e}"));
+        fs::write(&manifest, "(\n    models: None,\n)\n")
+            .unwrap_or_else(|e| panic!("Unable to write {MANIFEST}: {e}"));
+    }
+}
@@ -0,0 +1,317 @@
+//! Monte Carlo tree search, which needs no evaluation beyond who won a random playout.
+//!
+//! Every thread grows its own tree from the root, and the visit counts of the root moves are
+//! added together at the end, so more cores means more playouts without any locking.
+
+use rayon::prelude::*;
+use web_time::{Duration, Instant};
+
+use super::Search;
+use crate::{
+    variants::{Index, Outcome, Variant},
+    Player, MAX_PLAYER_COUNT,
+};
+
+/// How many moves a random playout may take before it is scored on the stores alone.
+const PLAYOUT_LIMIT: usize = 400;
+
+/// Searches with UCT, running a share of the iterations on every thread in the rayon pool.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MonteCarlo {
+    /// The total number of playouts, shared between all the threads.
+    pub iterations: u32,
+    /// How long the search may run before settling for the most visited move so far.
+    pub time_limit: Duration,
+    /// How strongly the search favours moves it hasn't looked at much.
+    pub exploration: f64,
+}
+
+impl Default for MonteCarlo {
+    fn default() -> Self {
+        Self {
+            iterations: 10_000,
+            time_limit: Duration::from_secs(1),
+            exploration: core::f64::consts::SQRT_2,
+        }
+    }
+}
+
+impl Search for MonteCarlo {
+    fn best_move(&self, variant: &dyn Variant) -> Option<Index> {
+        let moves = variant.legal_moves(variant.current_player());
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+
+        let visits = self.visits(variant, &moves);
+        tracing::debug!("Root visits {visits:?}");
+
+        moves
+            .into_iter()
+            .zip(visits)
+            .max_by_key(|&(_, visits)| visits)
+            .map(|(index, _)| index)
+    }
+}
+
+impl MonteCarlo {
+    /// Searches from `variant`, and returns how often each of the root `moves` was visited
+    /// across every thread.
+    fn visits(&self, variant: &dyn Variant, moves: &[Index]) -> Vec<u32> {
+        let deadline = Instant::now() + self.time_limit;
+        let threads = rayon::current_num_threads().max(1);
+        let iterations = self.iterations.div_ceil(threads as u32);
+
+        (0..threads)
+            .into_par_iter()
+            .map(|thread| {
+                let mut tree = Tree::new(variant, moves, thread as u64);
+                for _ in 0..iterations {
+                    if Instant::now() >= deadline {
+                        break;
+                    }
+                    tree.iterate(self.exploration);
+                }
+                tree.root_visits(moves)
+            })
+            .reduce(
+                || vec![0; moves.len()],
+                |mut total, visits| {
+                    total.iter_mut().zip(visits).for_each(|(t, v)| *t += v);
+                    total
+                },
+            )
+    }
+}
+
+struct Node {
+    /// The move that led here from the parent, or `None` for the root.
+    index: Option<Index>,
+    /// The player who made that move, and whose point of view `wins` is counted from.
+    mover: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<Index>,
+    visits: u32,
+    wins: f64,
+}
+
+/// A single search tree, along with its own copy of the game and random numbers.
+struct Tree {
+    nodes: Vec<Node>,
+    variant: Box<dyn Variant>,
+    rng: XorShift,
+}
+
+impl Tree {
+    fn new(variant: &dyn Variant, moves: &[Index], seed: u64) -> Self {
+        let root = Node {
+            index: None,
+            mover: Player::next(variant.current_player(), variant.player_count()),
+            parent: None,
+            children: Vec::new(),
+            untried: moves.to_vec(),
+            visits: 0,
+            wins: 0.0,
+        };
+        Self {
+            nodes: vec![root],
+            variant: variant.clone_box(),
+            rng: XorShift::new(seed),
+        }
+    }
+
+    /// Runs one round of selection, expansion, playout and backpropagation.
+    fn iterate(&mut self, exploration: f64) {
+        let mut node = 0;
+        let mut played = 0;
+
+        // Selection: follow the most promising children until one has moves left to try.
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select_child(node, exploration);
+            let index = self.nodes[node].index.expect("Only the root has no move");
+            self.variant.perform_move(index);
+            played += 1;
+        }
+
+        // Expansion: try one of the moves nobody has looked at yet.
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let index = untried.swap_remove(self.rng.below(untried.len()));
+            let mover = self.variant.current_player();
+            self.variant.perform_move(index);
+            played += 1;
+
+            let untried = if self.variant.outcome().is_some() {
+                Vec::new()
+            } else {
+                self.variant.legal_moves(self.variant.current_player())
+            };
+            let child = self.nodes.len();
+            self.nodes.push(Node {
+                index: Some(index),
+                mover,
+                parent: Some(node),
+                children: Vec::new(),
+                untried,
+                visits: 0,
+                wins: 0.0,
+            });
+            self.nodes[node].children.push(child);
+            node = child;
+        }
+
+        // Playout: pick moves at random until somebody wins.
+        let mut moves = 0;
+        while self.variant.outcome().is_none() && moves < PLAYOUT_LIMIT {
+            let legal = self.variant.legal_moves(self.variant.current_player());
+            if legal.is_empty() {
+                break;
+            }
+            self.variant
+                .perform_move(legal[self.rng.below(legal.len())]);
+            moves += 1;
+        }
+        let rewards = self.rewards();
+        for _ in 0..played + moves {
+            self.variant.undo_move();
+        }
+
+        // Backpropagation: credit every node on the way back up.
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.wins += rewards[*node.mover];
+            current = node.parent;
+        }
+    }
+
+    fn select_child(&self, node: usize, exploration: f64) -> usize {
+        let log_visits = f64::from(self.nodes[node].visits).ln();
+        let uct = |child: usize| {
+            let Node { visits, wins, .. } = self.nodes[child];
+            let visits = f64::from(visits);
+            wins / visits + exploration * (log_visits / visits).sqrt()
+        };
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .max_by(|&a, &b| uct(a).total_cmp(&uct(b)))
+            .expect("Selection only happens on nodes with children")
+    }
+
+    /// How well each player did at the end of the playout.
+    fn rewards(&self) -> [f64; MAX_PLAYER_COUNT] {
+        // If the playout was cut short, whoever has the most in their store is ahead.
+        let outcome = self
+            .variant
+            .outcome()
+            .unwrap_or_else(|| Outcome::from_stores(self.variant.position()));
+        core::array::from_fn(|p| outcome.reward(Player(p)))
+    }
+
+    /// How often each of the root `moves` was visited, in the same order.
+    fn root_visits(&self, moves: &[Index]) -> Vec<u32> {
+        moves
+            .iter()
+            .map(|&index| {
+                self.nodes[0]
+                    .children
+                    .iter()
+                    .map(|&child| &self.nodes[child])
+                    .find(|child| child.index == Some(index))
+                    .map_or(0, |child| child.visits)
+            })
+            .collect()
+    }
+}
+
+/// A small, fast random number generator; playouts don't need anything better.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // The state must never be zero, and nearby seeds should give unrelated sequences.
+        Self((seed + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    /// A random number in `0..bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        variants::kalah::{Kalah, Sweep},
+        Hole,
+    };
+
+    /// A game of Kalah from `position`, written in the crate's notation.
+    fn game(position: &str) -> Kalah {
+        let mut game = Kalah::new(6, 4, Sweep::ToOwner);
+        assert!(game.set_position(position.parse().unwrap()));
+        game
+    }
+
+    fn hole(player: usize, hole: usize) -> Index {
+        Index::Player(Player(player), Hole(hole))
+    }
+
+    #[test]
+    fn plays_the_only_legal_move() {
+        let game = game("0,0,0,0,0,1[10]/1,1,1,1,1,1[10] 0");
+        assert_eq!(MonteCarlo::default().best_move(&game), Some(hole(0, 5)));
+    }
+
+    #[test]
+    fn gives_up_without_a_legal_move() {
+        let game = game("0,0,0,0,0,0[24]/1,0,0,0,0,0[23] 0");
+        assert_eq!(MonteCarlo::default().best_move(&game), None);
+    }
+
+    #[test]
+    fn takes_a_winning_capture() {
+        // `A` captures the nine seeds on `e` and ends the game; `C` hands the opponent the move.
+        let game = game("1,0,3,0,0,0[10]/0,0,0,0,9,0[15] 0");
+        assert_eq!(MonteCarlo::default().best_move(&game), Some(hole(0, 0)));
+    }
+
+    #[test]
+    fn stops_after_its_iterations() {
+        let game = game("4,4,4,4,4,4[0]/4,4,4,4,4,4[0] 0");
+        let search = MonteCarlo {
+            iterations: 100,
+            time_limit: Duration::from_secs(60),
+            ..MonteCarlo::default()
+        };
+        let moves = game.legal_moves(Player(0));
+        let visits: u32 = search.visits(&game, &moves).iter().sum();
+
+        // Every thread runs its own share, rounded up.
+        let threads = rayon::current_num_threads().max(1) as u32;
+        assert!(visits >= 100);
+        assert!(visits <= 100u32.div_ceil(threads) * threads);
+    }
+
+    #[test]
+    fn stops_when_time_runs_out() {
+        let game = game("4,4,4,4,4,4[0]/4,4,4,4,4,4[0] 0");
+        let search = MonteCarlo {
+            iterations: u32::MAX,
+            time_limit: Duration::from_millis(50),
+            ..MonteCarlo::default()
+        };
+        let start = Instant::now();
+
+        assert!(search.best_move(&game).is_some());
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
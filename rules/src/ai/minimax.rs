@@ -0,0 +1,203 @@
+//! A classic alpha-beta search, scoring positions by the difference between the stores.
+
+use web_time::{Duration, Instant};
+
+use super::Search;
+use crate::{
+    variants::{Index, Outcome, Variant},
+    Player,
+};
+
+/// The score of a won game, before the store difference is added on.
+const WIN: i32 = 10_000;
+
+/// How many nodes are searched between looks at the clock.
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// Searches the game tree with alpha-beta pruning, one ply deeper at a time, until it reaches
+/// `depth` or runs out of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Minimax {
+    /// The furthest ahead the search will look, in moves.
+    pub depth: u32,
+    /// How long the search may run before settling for the best move found so far.
+    pub time_budget: Duration,
+}
+
+impl Search for Minimax {
+    fn best_move(&self, variant: &dyn Variant) -> Option<Index> {
+        let me = variant.current_player();
+        let mut moves = variant.legal_moves(me);
+        if moves.len() <= 1 {
+            return moves.first().copied();
+        }
+
+        let mut variant = variant.clone_box();
+        let mut search = AlphaBeta {
+            me,
+            deadline: Instant::now() + self.time_budget,
+            nodes: 0,
+            timed_out: false,
+        };
+
+        let mut best = moves[0];
+        for depth in 1..=self.depth.max(1) {
+            // A search cut short by the clock can't be trusted, so keep the last complete one.
+            let Some(index) = search.root(&mut *variant, &moves, depth) else {
+                break;
+            };
+            best = index;
+            // Looking at the best move first lets the next, deeper search prune the most.
+            moves.retain(|&index| index != best);
+            moves.insert(0, best);
+        }
+        tracing::debug!("Searched {} positions", search.nodes);
+
+        Some(best)
+    }
+}
+
+struct AlphaBeta {
+    me: Player,
+    deadline: Instant,
+    nodes: u64,
+    timed_out: bool,
+}
+
+impl AlphaBeta {
+    fn root(&mut self, variant: &mut dyn Variant, moves: &[Index], depth: u32) -> Option<Index> {
+        let mut alpha = -i32::MAX;
+        let mut best = None;
+        for &index in moves {
+            variant.perform_move(index);
+            let score = self.alpha_beta(variant, depth - 1, alpha, i32::MAX);
+            variant.undo_move();
+            if self.timed_out {
+                return None;
+            }
+            if best.is_none() || score > alpha {
+                best = Some(index);
+                alpha = score;
+            }
+        }
+        best
+    }
+
+    fn alpha_beta(
+        &mut self,
+        variant: &mut dyn Variant,
+        depth: u32,
+        mut alpha: i32,
+        mut beta: i32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(CLOCK_INTERVAL) && Instant::now() >= self.deadline {
+            self.timed_out = true;
+        }
+        if self.timed_out {
+            return 0;
+        }
+
+        if let Some(outcome) = variant.outcome() {
+            // Prefer the quickest win, and the slowest loss.
+            let win = WIN + depth as i32;
+            return match outcome {
+                Outcome::Winner(winner) if winner == self.me => win,
+                Outcome::Winner(_) => -win,
+                Outcome::Draw => 0,
+            } + self.evaluate(variant);
+        }
+        let player = variant.current_player();
+        let moves = variant.legal_moves(player);
+        if depth == 0 || moves.is_empty() {
+            return self.evaluate(variant);
+        }
+
+        // Turns don't always alternate, so whose turn it is decides which way we're looking.
+        let maximizing = player == self.me;
+        let mut best = if maximizing { -i32::MAX } else { i32::MAX };
+        for index in moves {
+            variant.perform_move(index);
+            let score = self.alpha_beta(variant, depth - 1, alpha, beta);
+            variant.undo_move();
+            if maximizing {
+                best = best.max(score);
+                alpha = alpha.max(best);
+            } else {
+                best = best.min(score);
+                beta = beta.min(best);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+        best
+    }
+
+    /// How far ahead our store is of everybody else's.
+    fn evaluate(&self, variant: &dyn Variant) -> i32 {
+        let position = variant.position();
+        (0..position.player_count())
+            .map(|p| {
+                let store = position.store(Player(p)) as i32;
+                if Player(p) == self.me {
+                    store
+                } else {
+                    -store
+                }
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        variants::kalah::{Kalah, Sweep},
+        Hole,
+    };
+
+    const SEARCH: Minimax = Minimax {
+        depth: 6,
+        time_budget: Duration::from_secs(10),
+    };
+
+    /// A game of Kalah from `position`, written in the crate's notation.
+    fn game(position: &str) -> Kalah {
+        let mut game = Kalah::new(6, 4, Sweep::ToOwner);
+        assert!(game.set_position(position.parse().unwrap()));
+        game
+    }
+
+    fn hole(player: usize, hole: usize) -> Index {
+        Index::Player(Player(player), Hole(hole))
+    }
+
+    #[test]
+    fn plays_the_only_legal_move() {
+        let game = game("0,0,0,0,0,1[10]/1,1,1,1,1,1[10] 0");
+        assert_eq!(SEARCH.best_move(&game), Some(hole(0, 5)));
+    }
+
+    #[test]
+    fn gives_up_without_a_legal_move() {
+        let game = game("0,0,0,0,0,0[24]/1,0,0,0,0,0[23] 0");
+        assert_eq!(SEARCH.best_move(&game), None);
+    }
+
+    #[test]
+    fn takes_a_winning_capture() {
+        // `A` captures the nine seeds on `e` and ends the game; `C` hands the opponent the move.
+        let game = game("1,0,3,0,0,0[10]/0,0,0,0,9,0[15] 0");
+        assert_eq!(SEARCH.best_move(&game), Some(hole(0, 0)));
+    }
+
+    #[test]
+    fn takes_a_large_capture_over_a_quiet_move() {
+        // `A` captures the five seeds on `e`; `F` only drops a seed in the store and on `a`.
+        let game = game("1,0,0,0,0,2[0]/1,1,1,1,5,1[0] 0");
+        let shallow = Minimax { depth: 1, ..SEARCH };
+        assert_eq!(shallow.best_move(&game), Some(hole(0, 0)));
+    }
+}
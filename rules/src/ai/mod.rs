@@ -0,0 +1,17 @@
+//! Computer opponents, which pick their moves by searching ahead through the rules.
+
+use core::fmt::Debug;
+
+use crate::variants::{Index, Variant};
+
+pub mod mcts;
+pub mod minimax;
+
+pub use mcts::MonteCarlo;
+pub use minimax::Minimax;
+
+/// A way of picking moves that works for any [`Variant`].
+pub trait Search: Send + Sync + Debug {
+    /// The best move for the player whose turn it is, or `None` if they cannot move.
+    fn best_move(&self, variant: &dyn Variant) -> Option<Index>;
+}
@@ -0,0 +1,94 @@
+//! The rules of the mancala games played in `stones`.
+//!
+//! Everything in here works on plain seed counts, without a Bevy `World`, so the rules can be
+//! searched by an AI, tested and used by tooling without spinning up the renderer. Moves report
+//! what happened as a list of [`variants::MoveEvent`]s, which the game turns into animations.
+
+extern crate alloc;
+
+use core::ops::{Deref, DerefMut};
+
+pub mod ai;
+pub mod notation;
+pub mod position;
+pub mod variants;
+
+pub use position::{Position, Side};
+
+/// The number of players in a standard game, and in every variant built into the game.
+pub const PLAYER_COUNT: usize = 2;
+
+/// The most players any variant may have.
+pub const MAX_PLAYER_COUNT: usize = 4;
+
+/// The number of holes in each row of a standard board.
+pub const HOLE_COUNT: usize = 6;
+
+/// The most holes any variant may have in each row.
+pub const MAX_HOLE_COUNT: usize = 8;
+
+/// The most rows of holes any variant may give each player, as on the four-row boards of Bao and
+/// Omweso.
+pub const MAX_ROW_COUNT: usize = 2;
+
+/// A player, numbered from `0`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy",
+    derive(bevy_ecs::component::Component, bevy_reflect::Reflect)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Player(pub usize);
+
+impl Player {
+    /// The player whose turn comes after `this` one, in a game of `player_count` players.
+    pub fn next(this: impl Into<Self>, player_count: usize) -> Self {
+        Self((this.into().0 + 1) % player_count)
+    }
+}
+
+impl From<usize> for Player {
+    fn from(value: usize) -> Self {
+        Self(value)
+    }
+}
+
+impl Deref for Player {
+    type Target = usize;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Player {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+/// A hole on a player's side of the board, numbered in sowing order from `0`.
+///
+/// On boards with more than one row a side, the numbering carries on from the end of each row
+/// into the start of the next, so a player's holes are numbered the same way on every board.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "bevy",
+    derive(bevy_ecs::component::Component, bevy_reflect::Reflect)
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Hole(pub usize);
+
+impl Deref for Hole {
+    type Target = usize;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Hole {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
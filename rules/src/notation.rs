@@ -0,0 +1,435 @@
+//! A compact, human-readable notation for moves and positions, for bug reports, puzzles and
+//! sharing games over chat.
+//!
+//! Holes are written as letters in sowing order, upper case for player `0` and lower case for
+//! player `1`, so `A` is player `0`'s first hole and `f` is player `1`'s sixth. Stores are written
+//! `S` and `s`. Any further players write their number before an upper case letter, so `2C` is
+//! player `2`'s third hole. A list of moves is written as these letters separated by spaces.
+//!
+//! A position lists each player's holes in sowing order, separated by commas, with their store
+//! in brackets after them. Players with more than one row separate their rows with `|`. The
+//! players' sides are separated by slashes, and followed by the number of the player to move, so
+//! the starting position of Kalah(6, 4) is:
+//!
+//! ```text
+//! 4,4,4,4,4,4[0]/4,4,4,4,4,4[0] 1
+//! ```
+//!
+//! Printing and then parsing any move or position gives back exactly what was printed.
+
+use core::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{
+    variants::Index, Hole, Player, Position, MAX_HOLE_COUNT, MAX_PLAYER_COUNT, MAX_ROW_COUNT,
+};
+
+/// Why a move or position couldn't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The text was not a hole or store letter.
+    InvalidMove(String),
+    /// The wrong number of sides or rows, or no side to move.
+    InvalidLayout(String),
+    /// A seed count was not a number.
+    InvalidCount(String),
+    /// The rows had different numbers of holes, or more than any board has.
+    InvalidHoleCount(usize),
+    /// The side to move was not one of the players.
+    InvalidTurn(String),
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidMove(text) => write!(f, "Not a hole or store: {text:?}"),
+            ParseError::InvalidLayout(text) => write!(f, "Not a position: {text:?}"),
+            ParseError::InvalidCount(text) => write!(f, "Not a seed count: {text:?}"),
+            ParseError::InvalidHoleCount(count) => {
+                write!(f, "Rows must all have the same number of holes, up to {MAX_HOLE_COUNT}, not {count}")
+            }
+            ParseError::InvalidTurn(text) => write!(f, "Not a player: {text:?}"),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// The letters the first player's holes are written with, in sowing order.
+const HOLE_LETTERS: &[u8; MAX_ROW_COUNT * MAX_HOLE_COUNT] = b"ABCDEFGHIJKLMNOP";
+
+/// The letter each player's store is written with.
+const STORE_LETTER: u8 = b'S';
+
+/// Writes `letter` in upper case for player `0`, in lower case for player `1`, and after their
+/// number for everyone else.
+fn write_for_player(f: &mut Formatter<'_>, letter: u8, Player(player): Player) -> fmt::Result {
+    match player {
+        0 => write!(f, "{}", char::from(letter)),
+        1 => write!(f, "{}", char::from(letter.to_ascii_lowercase())),
+        _ => write!(f, "{player}{}", char::from(letter)),
+    }
+}
+
+impl Display for Index {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Index::Player(player, Hole(h)) => write_for_player(f, HOLE_LETTERS[h], player),
+            Index::Score(player) => write_for_player(f, STORE_LETTER, player),
+        }
+    }
+}
+
+impl FromStr for Index {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidMove(s.to_string());
+        let (player, letter) = match *s.as_bytes() {
+            [letter] if letter.is_ascii_uppercase() => (Player(0), letter),
+            [letter] => (Player(1), letter.to_ascii_uppercase()),
+            [digit @ b'2'..=b'9', letter] if letter.is_ascii_uppercase() => {
+                (Player(usize::from(digit - b'0')), letter)
+            }
+            _ => return Err(invalid()),
+        };
+        if *player >= MAX_PLAYER_COUNT {
+            return Err(invalid());
+        }
+
+        if letter == STORE_LETTER {
+            return Ok(Index::Score(player));
+        }
+        HOLE_LETTERS
+            .iter()
+            .position(|&l| l == letter)
+            .map(|h| Index::Player(player, Hole(h)))
+            .ok_or_else(invalid)
+    }
+}
+
+/// Writes a list of moves, separated by spaces.
+pub fn format_moves(moves: &[Index]) -> String {
+    moves
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reads a list of moves, separated by whitespace.
+pub fn parse_moves(s: &str) -> Result<Vec<Index>, ParseError> {
+    s.split_whitespace().map(str::parse).collect()
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (p, side) in self.sides().iter().enumerate() {
+            if p > 0 {
+                write!(f, "/")?;
+            }
+            for (h, seeds) in side.holes[..self.side_hole_count()].iter().enumerate() {
+                if h > 0 {
+                    let separator = if h % self.hole_count() == 0 { '|' } else { ',' };
+                    write!(f, "{separator}")?;
+                }
+                write!(f, "{seeds}")?;
+            }
+            write!(f, "[{}]", side.store)?;
+        }
+        write!(f, " {}", *self.turn())
+    }
+}
+
+impl FromStr for Position {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let layout = || ParseError::InvalidLayout(s.to_string());
+        let (rows, turn) = s.trim().split_once(' ').ok_or_else(layout)?;
+
+        let sides = rows
+            .split('/')
+            .map(parse_side)
+            .collect::<Result<Vec<_>, _>>()?;
+        if !(2..=MAX_PLAYER_COUNT).contains(&sides.len()) {
+            return Err(layout());
+        }
+        let turn = turn
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&turn| turn < sides.len())
+            .ok_or_else(|| ParseError::InvalidTurn(turn.to_string()))?;
+
+        let row_count = sides[0].0.len();
+        if row_count > MAX_ROW_COUNT || sides.iter().any(|(rows, _)| rows.len() != row_count) {
+            return Err(layout());
+        }
+        let hole_count = sides[0].0[0].len();
+        if let Some(holes) = sides
+            .iter()
+            .flat_map(|(rows, _)| rows)
+            .find(|holes| holes.len() != hole_count || holes.len() > MAX_HOLE_COUNT)
+        {
+            return Err(ParseError::InvalidHoleCount(holes.len()));
+        }
+
+        let mut position = Position::with_rows(sides.len(), row_count, hole_count, 0, Player(turn));
+        for (p, (rows, store)) in sides.into_iter().enumerate() {
+            for (h, seeds) in rows.into_iter().flatten().enumerate() {
+                *position.seeds_mut(Index::Player(Player(p), Hole(h))) = seeds;
+            }
+            *position.seeds_mut(Index::Score(Player(p))) = store;
+        }
+        Ok(position)
+    }
+}
+
+/// Reads one player's side, such as `4,4,4,4,4,4[0]`, into its rows of holes and its store.
+fn parse_side(side: &str) -> Result<(Vec<Vec<usize>>, usize), ParseError> {
+    let layout = || ParseError::InvalidLayout(side.to_string());
+    let (rows, store) = side.split_once('[').ok_or_else(layout)?;
+    let store = store.strip_suffix(']').ok_or_else(layout)?;
+
+    let count = |text: &str| {
+        text.trim()
+            .parse::<usize>()
+            .map_err(|_| ParseError::InvalidCount(text.to_string()))
+    };
+    let rows = rows
+        .split('|')
+        .map(|row| row.split(',').map(count).collect::<Result<Vec<_>, _>>())
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok((rows, count(store)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::variants::{
+        custom::{Circuit, Custom, Definition},
+        kalah::Kalah,
+        oware::Oware,
+        Variant,
+    };
+
+    /// Checks that `text` parses as `T`, and prints back exactly as it was written.
+    fn round_trip<T>(text: &str) -> T
+    where
+        T: FromStr<Err = ParseError> + Display,
+    {
+        let parsed = text.parse::<T>().unwrap();
+        assert_eq!(parsed.to_string(), text);
+        parsed
+    }
+
+    #[test]
+    fn holes_and_stores_round_trip() {
+        let cases = [
+            ("A", Index::Player(Player(0), Hole(0))),
+            ("F", Index::Player(Player(0), Hole(5))),
+            ("P", Index::Player(Player(0), Hole(15))),
+            ("S", Index::Score(Player(0))),
+            ("a", Index::Player(Player(1), Hole(0))),
+            ("h", Index::Player(Player(1), Hole(7))),
+            ("s", Index::Score(Player(1))),
+            ("2C", Index::Player(Player(2), Hole(2))),
+            ("3P", Index::Player(Player(3), Hole(15))),
+            ("2S", Index::Score(Player(2))),
+            ("3S", Index::Score(Player(3))),
+        ];
+        for (text, index) in cases {
+            assert_eq!(round_trip::<Index>(text), index);
+        }
+    }
+
+    #[test]
+    fn every_index_round_trips() {
+        for p in 0..MAX_PLAYER_COUNT {
+            let holes =
+                (0..MAX_ROW_COUNT * MAX_HOLE_COUNT).map(|h| Index::Player(Player(p), Hole(h)));
+            for index in holes.chain([Index::Score(Player(p))]) {
+                assert_eq!(index.to_string().parse::<Index>(), Ok(index));
+            }
+        }
+    }
+
+    #[test]
+    fn move_lists_round_trip() {
+        let text = "C f S 2B 3S a";
+        let moves = parse_moves(text).unwrap();
+        assert_eq!(
+            moves,
+            [
+                Index::Player(Player(0), Hole(2)),
+                Index::Player(Player(1), Hole(5)),
+                Index::Score(Player(0)),
+                Index::Player(Player(2), Hole(1)),
+                Index::Score(Player(3)),
+                Index::Player(Player(1), Hole(0)),
+            ]
+        );
+        assert_eq!(format_moves(&moves), text);
+        assert_eq!(parse_moves(""), Ok(Vec::new()));
+        assert_eq!(format_moves(&[]), "");
+        // Any whitespace separates moves, but they are always written with single spaces.
+        assert_eq!(format_moves(&parse_moves("  A\tb\n C ").unwrap()), "A b C");
+    }
+
+    #[test]
+    fn played_games_round_trip() {
+        let mut game = Kalah::default();
+        while game.outcome().is_none() {
+            let index = game.legal_moves(game.current_player())[0];
+            game.perform_move(index);
+        }
+        let text = format_moves(&game.moves());
+        assert_eq!(parse_moves(&text), Ok(game.moves()));
+    }
+
+    #[test]
+    fn one_row_positions_round_trip() {
+        let start = round_trip::<Position>("4,4,4,4,4,4[0]/4,4,4,4,4,4[0] 1");
+        assert_eq!(start, *Kalah::default().position());
+        assert_eq!(start, *Oware::default().position());
+
+        let position = round_trip::<Position>("0,12,1,0[7]/3,0,0,2[9] 0");
+        assert_eq!(position.hole_count(), 4);
+        assert_eq!(position.row_count(), 1);
+        assert_eq!(position.seeds(Index::Player(Player(0), Hole(1))), 12);
+        assert_eq!(position.seeds(Index::Score(Player(1))), 9);
+        assert_eq!(position.turn(), Player(0));
+
+        let four_players = round_trip::<Position>("1,2,3[4]/5,6,7[8]/9,10,11[12]/13,14,15[16] 3");
+        assert_eq!(four_players.player_count(), 4);
+        assert_eq!(four_players.seeds(Index::Player(Player(3), Hole(2))), 15);
+        assert_eq!(four_players.seeds(Index::Score(Player(2))), 12);
+    }
+
+    #[test]
+    fn multi_row_positions_round_trip() {
+        let position = round_trip::<Position>("1,2,3|4,5,6[7]/8,9,10|11,12,13[14] 0");
+        assert_eq!(position.row_count(), 2);
+        assert_eq!(position.hole_count(), 3);
+        // The second row carries on the numbering from the first.
+        assert_eq!(position.seeds(Index::Player(Player(0), Hole(3))), 4);
+        assert_eq!(position.seeds(Index::Player(Player(1), Hole(5))), 13);
+    }
+
+    #[test]
+    fn variant_positions_round_trip() {
+        let custom = Custom::new(Definition {
+            name: "Four rows".to_string(),
+            description: String::new(),
+            players: 2,
+            rows: 2,
+            holes: 8,
+            seeds: 2,
+            direction: Default::default(),
+            circuit: Circuit::Own,
+            stores: Default::default(),
+            skip_origin: false,
+            relay: true,
+            extra_turn: false,
+            captures: Vec::new(),
+            grand_slam: None,
+            must_feed: false,
+            end: Default::default(),
+            majority: false,
+            sweep: Default::default(),
+        })
+        .unwrap();
+        let mut games: [Box<dyn Variant>; 3] = [
+            Box::new(Kalah::default()),
+            Box::new(Oware::default()),
+            Box::new(custom),
+        ];
+        for game in &mut games {
+            for _ in 0..6 {
+                let Some(&index) = game.legal_moves(game.current_player()).last() else {
+                    break;
+                };
+                game.perform_move(index);
+                let text = game.position().to_string();
+                assert_eq!(round_trip::<Position>(&text), *game.position());
+            }
+        }
+    }
+
+    #[test]
+    fn bad_moves_are_rejected() {
+        for text in ["", "Z", "z", "AB", "1A", "2a", "4A", "9S", "A2", "?"] {
+            assert_eq!(
+                text.parse::<Index>(),
+                Err(ParseError::InvalidMove(text.to_string())),
+                "{text:?}"
+            );
+        }
+        assert_eq!(
+            parse_moves("A b Q? c"),
+            Err(ParseError::InvalidMove("Q?".to_string()))
+        );
+    }
+
+    #[test]
+    fn bad_positions_are_rejected() {
+        let cases = [
+            // No side to move.
+            (
+                "4,4[0]/4,4[0]",
+                ParseError::InvalidLayout("4,4[0]/4,4[0]".to_string()),
+            ),
+            // Only one side.
+            (
+                "4,4[0] 0",
+                ParseError::InvalidLayout("4,4[0] 0".to_string()),
+            ),
+            // Too many sides.
+            (
+                "1[0]/1[0]/1[0]/1[0]/1[0] 0",
+                ParseError::InvalidLayout("1[0]/1[0]/1[0]/1[0]/1[0] 0".to_string()),
+            ),
+            // A side without a store.
+            ("4,4/4,4[0] 0", ParseError::InvalidLayout("4,4".to_string())),
+            (
+                "4,4[0/4,4[0] 0",
+                ParseError::InvalidLayout("4,4[0".to_string()),
+            ),
+            // Sides with different numbers of rows.
+            (
+                "4|4[0]/4[0] 0",
+                ParseError::InvalidLayout("4|4[0]/4[0] 0".to_string()),
+            ),
+            // More rows than any board has.
+            (
+                "1|1|1[0]/1|1|1[0] 0",
+                ParseError::InvalidLayout("1|1|1[0]/1|1|1[0] 0".to_string()),
+            ),
+            ("4,x[0]/4,4[0] 1", ParseError::InvalidCount("x".to_string())),
+            (
+                "4,4[-1]/4,4[0] 1",
+                ParseError::InvalidCount("-1".to_string()),
+            ),
+            (
+                "4,,4[0]/4,4,4[0] 1",
+                ParseError::InvalidCount(String::new()),
+            ),
+            ("4,4[0]/4[0] 0", ParseError::InvalidHoleCount(1)),
+            (
+                "1,1,1,1,1,1,1,1,1[0]/1,1,1,1,1,1,1,1,1[0] 0",
+                ParseError::InvalidHoleCount(9),
+            ),
+            ("4,4[0]/4,4[0] 2", ParseError::InvalidTurn("2".to_string())),
+            (
+                "4,4[0]/4,4[0] one",
+                ParseError::InvalidTurn("one".to_string()),
+            ),
+        ];
+        for (text, error) in cases {
+            assert_eq!(text.parse::<Position>(), Err(error), "{text:?}");
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! The seed counts on the board, independent of any particular variant.
+
+use crate::{
+    variants::Index, Hole, Player, MAX_HOLE_COUNT, MAX_PLAYER_COUNT, MAX_ROW_COUNT, PLAYER_COUNT,
+};
+
+/// The seeds on one player's side of the board.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Side {
+    /// The number of seeds in each hole, in sowing order across every row. Holes past the end of
+    /// the board are always empty.
+    pub holes: [usize; MAX_ROW_COUNT * MAX_HOLE_COUNT],
+    /// The number of seeds in the player's store.
+    pub store: usize,
+}
+
+/// A snapshot of the whole board, along with whose turn it is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    /// Only the first `player_count` sides are on the board.
+    sides: [Side; MAX_PLAYER_COUNT],
+    player_count: usize,
+    row_count: usize,
+    hole_count: usize,
+    turn: Player,
+}
+
+impl Position {
+    /// A two-player board with one row of `hole_count` holes on each side, `seeds` in every hole,
+    /// and `first` to move.
+    pub fn new(hole_count: usize, seeds: usize, first: Player) -> Self {
+        Self::with_rows(PLAYER_COUNT, 1, hole_count, seeds, first)
+    }
+
+    /// A board for `player_count` players, each with `row_count` rows of `hole_count` holes,
+    /// `seeds` in every hole, and `first` to move.
+    pub fn with_rows(
+        player_count: usize,
+        row_count: usize,
+        hole_count: usize,
+        seeds: usize,
+        first: Player,
+    ) -> Self {
+        assert!(player_count <= MAX_PLAYER_COUNT, "Too many players");
+        assert!(row_count <= MAX_ROW_COUNT, "Too many rows");
+        assert!(hole_count <= MAX_HOLE_COUNT, "Too many holes");
+        let mut holes = [0; MAX_ROW_COUNT * MAX_HOLE_COUNT];
+        holes[..row_count * hole_count].fill(seeds);
+        let mut sides = [Side::default(); MAX_PLAYER_COUNT];
+        sides[..player_count].fill(Side { holes, store: 0 });
+        Self {
+            sides,
+            player_count,
+            row_count,
+            hole_count,
+            turn: first,
+        }
+    }
+
+    /// The number of holes in each row of the board.
+    pub fn hole_count(&self) -> usize {
+        self.hole_count
+    }
+
+    /// The number of rows of holes each player has.
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// The number of holes on each player's side, across all of their rows.
+    pub fn side_hole_count(&self) -> usize {
+        self.row_count * self.hole_count
+    }
+
+    /// The number of players around the board.
+    pub fn player_count(&self) -> usize {
+        self.player_count
+    }
+
+    /// Whether `other` has the same players, rows and holes, whatever seeds are in them.
+    pub fn same_layout(&self, other: &Position) -> bool {
+        (self.player_count, self.row_count, self.hole_count)
+            == (other.player_count, other.row_count, other.hole_count)
+    }
+
+    /// The player whose turn comes after `player`'s.
+    pub fn next_player(&self, player: Player) -> Player {
+        Player::next(player, self.player_count)
+    }
+
+    /// The player whose turn it is.
+    pub fn turn(&self) -> Player {
+        self.turn
+    }
+
+    /// Hands the turn to `player`.
+    pub fn set_turn(&mut self, player: Player) {
+        self.turn = player;
+    }
+
+    /// Every side of the board, indexed by player.
+    pub fn sides(&self) -> &[Side] {
+        &self.sides[..self.player_count]
+    }
+
+    /// The number of seeds in the hole or store at `index`.
+    pub fn seeds(&self, index: Index) -> usize {
+        match index {
+            Index::Player(Player(p), Hole(h)) => self.sides[p].holes[h],
+            Index::Score(Player(p)) => self.sides[p].store,
+        }
+    }
+
+    /// A mutable reference to the number of seeds at `index`.
+    pub fn seeds_mut(&mut self, index: Index) -> &mut usize {
+        match index {
+            Index::Player(Player(p), Hole(h)) => &mut self.sides[p].holes[h],
+            Index::Score(Player(p)) => &mut self.sides[p].store,
+        }
+    }
+
+    /// Empties the hole or store at `index`, returning how many seeds it held.
+    pub fn take(&mut self, index: Index) -> usize {
+        core::mem::take(self.seeds_mut(index))
+    }
+
+    /// The number of seeds left in `player`'s holes, not counting their store.
+    pub fn row_total(&self, Player(player): Player) -> usize {
+        self.sides[player].holes.iter().sum()
+    }
+
+    /// The number of seeds in `player`'s store.
+    pub fn store(&self, Player(player): Player) -> usize {
+        self.sides[player].store
+    }
+}
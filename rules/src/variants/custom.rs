@@ -0,0 +1,855 @@
+//! Variants described by a [`Definition`] rather than written in Rust, so more of the mancala
+//! family can be played without recompiling the game.
+//!
+//! A definition picks from the rules most of the family shares: how many players, rows, holes
+//! and seeds, which holes and stores are sown into, whether sowing relays on from the hole it
+//! ends in, how seeds are captured and when the game is over. [`Custom`] plays any definition that
+//! [validates](Definition::validate).
+
+use alloc::sync::Arc;
+use core::fmt::{self, Display, Formatter};
+
+use super::{kalah::Sweep, oware::GrandSlam, Direction, Index, MoveEvent, Outcome, Variant};
+use crate::{Hole, Player, Position, MAX_HOLE_COUNT, MAX_PLAYER_COUNT, MAX_ROW_COUNT};
+
+/// The most times a relay sowing may lift the seeds back out of the hole it ended in, in case a
+/// definition allows sowing that would never end.
+const MAX_RELAYS: usize = 1000;
+
+/// Which stores seeds are sown into as they pass them.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Stores {
+    /// Both stores are passed over, so seeds only reach them by being captured.
+    Skip,
+    /// The mover's own store is sown into, and their opponent's is passed over.
+    #[default]
+    Own,
+    /// Every store is sown into.
+    All,
+}
+
+/// The holes a sowing travels around.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Circuit {
+    /// Around the whole board, through every player's holes.
+    #[default]
+    Board,
+    /// Around the mover's own rows only, never reaching a store, as on four-row boards.
+    Own,
+}
+
+/// Whose holes a rule applies to, from the mover's point of view.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Whose {
+    /// Only the mover's own holes.
+    #[default]
+    Own,
+    /// Only the opponent's holes.
+    Opponent,
+    /// Any hole on the board.
+    Either,
+}
+
+impl Whose {
+    fn includes(self, Player(mover): Player, owner: usize) -> bool {
+        match self {
+            Whose::Own => owner == mover,
+            Whose::Opponent => owner != mover,
+            Whose::Either => true,
+        }
+    }
+}
+
+/// A way of capturing seeds once the last seed of a move has been sown.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Capture {
+    /// Ending in an empty hole captures the last seed, along with the seeds in the hole
+    /// opposite. Nothing is captured if the hole opposite is empty.
+    Opposite {
+        /// Whose holes the last seed has to land in.
+        #[cfg_attr(feature = "serde", serde(default))]
+        row: Whose,
+    },
+    /// Ending in a hole that then holds one of `counts` seeds captures them.
+    Counts {
+        /// The seed counts that are captured.
+        counts: Vec<usize>,
+        /// Whose holes the last seed has to land in.
+        row: Whose,
+        /// Whether the holes before it on the same row are captured too, for as long as each of
+        /// them holds one of `counts` seeds.
+        #[cfg_attr(feature = "serde", serde(default))]
+        chained: bool,
+    },
+}
+
+/// When the game is over.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum End {
+    /// As soon as any player's row is empty.
+    #[default]
+    RowEmpty,
+    /// Once the player to move has no legal move.
+    NoMoves,
+}
+
+/// The rules of a variant, as read from a variant file.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Definition {
+    /// The name the variant is known by.
+    pub name: String,
+    /// A sentence or two on how the variant is played.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    /// The number of players around the board.
+    #[cfg_attr(feature = "serde", serde(default = "standard_players"))]
+    pub players: usize,
+    /// The number of rows of holes each player has.
+    #[cfg_attr(feature = "serde", serde(default = "one_row"))]
+    pub rows: usize,
+    /// The number of holes in each row.
+    pub holes: usize,
+    /// The number of seeds in every hole at the start of the game.
+    pub seeds: usize,
+    /// Which way round the board the seeds are sown.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub direction: Direction,
+    /// The holes a sowing travels around.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub circuit: Circuit,
+    /// Which stores are sown into, when sowing around the whole board.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub stores: Stores,
+    /// Whether a sowing that laps the board passes over the hole it was lifted from.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skip_origin: bool,
+    /// Whether a sowing that ends in an occupied hole lifts every seed from it and carries on.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub relay: bool,
+    /// Whether ending in the mover's own store earns them another turn.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extra_turn: bool,
+    /// The ways of capturing seeds. Only the first that captures anything is used.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub captures: Vec<Capture>,
+    /// What happens when a move would capture every seed on the opponent's row, or `None` if
+    /// those moves capture like any other.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub grand_slam: Option<GrandSlam>,
+    /// Whether a player must leave the next player something to play with, if they are able to.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub must_feed: bool,
+    /// When the game is over.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub end: End,
+    /// Whether capturing more than half of the seeds ends the game early.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub majority: bool,
+    /// Whose store the seeds left on the board go to, once the game is over.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sweep: Sweep,
+}
+
+/// Two players, unless the definition says otherwise.
+#[cfg(feature = "serde")]
+fn standard_players() -> usize {
+    crate::PLAYER_COUNT
+}
+
+/// One row a player, unless the definition says otherwise.
+#[cfg(feature = "serde")]
+fn one_row() -> usize {
+    1
+}
+
+/// Why a definition can't be played.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DefinitionError {
+    /// Fewer than two players, or more than any board has room for.
+    InvalidPlayerCount(usize),
+    /// No rows of holes, or more than any board has.
+    InvalidRowCount(usize),
+    /// The rows have no holes, or more than any board has.
+    InvalidHoleCount(usize),
+    /// The holes would start out empty.
+    NoSeeds,
+    /// A counting capture has nothing to count, or counts empty holes.
+    InvalidCounts(Vec<usize>),
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DefinitionError::InvalidPlayerCount(count) => {
+                write!(
+                    f,
+                    "Boards need 2 to {MAX_PLAYER_COUNT} players, not {count}"
+                )
+            }
+            DefinitionError::InvalidRowCount(count) => {
+                write!(
+                    f,
+                    "Boards need 1 to {MAX_ROW_COUNT} rows a player, not {count}"
+                )
+            }
+            DefinitionError::InvalidHoleCount(count) => {
+                write!(
+                    f,
+                    "Boards need 1 to {MAX_HOLE_COUNT} holes a side, not {count}"
+                )
+            }
+            DefinitionError::NoSeeds => write!(f, "Every hole needs at least one seed"),
+            DefinitionError::InvalidCounts(counts) => {
+                write!(f, "Captures need seed counts above zero, not {counts:?}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for DefinitionError {}
+
+impl Definition {
+    /// Checks that the definition describes a game that can be played.
+    pub fn validate(&self) -> Result<(), DefinitionError> {
+        if !(2..=MAX_PLAYER_COUNT).contains(&self.players) {
+            return Err(DefinitionError::InvalidPlayerCount(self.players));
+        }
+        if !(1..=MAX_ROW_COUNT).contains(&self.rows) {
+            return Err(DefinitionError::InvalidRowCount(self.rows));
+        }
+        if !(1..=MAX_HOLE_COUNT).contains(&self.holes) {
+            return Err(DefinitionError::InvalidHoleCount(self.holes));
+        }
+        if self.seeds == 0 {
+            return Err(DefinitionError::NoSeeds);
+        }
+        for capture in &self.captures {
+            if let Capture::Counts { counts, .. } = capture {
+                if counts.is_empty() || counts.contains(&0) {
+                    return Err(DefinitionError::InvalidCounts(counts.clone()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A variant played by following a [`Definition`].
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct Custom {
+    /// Shared, since the AI clones the game for every line it searches.
+    definition: Arc<Definition>,
+    position: Position,
+    /// Every move played so far, along with the position it was played from.
+    history: Vec<(Index, Position)>,
+}
+
+/// The result of sowing from a single hole, before anything is captured.
+struct Sowing {
+    /// The board once every seed has been sown.
+    position: Position,
+    /// Every seed lifted and sown, in order.
+    events: Vec<MoveEvent>,
+    /// Where the last seed landed.
+    last: Index,
+    /// The holes that would be captured.
+    captures: Vec<Index>,
+    /// Whether the captures would take every seed from the opponent's row.
+    grand_slam: bool,
+}
+
+impl Sowing {
+    /// The number of seeds left on `player`'s row once the move is complete.
+    fn remaining(&self, player: Player, captures_allowed: bool) -> usize {
+        let captured = if captures_allowed {
+            self.captures
+                .iter()
+                .filter(|index| index.player() == *player)
+                .map(|&index| self.position.seeds(index))
+                .sum()
+        } else {
+            0
+        };
+        self.position.row_total(player) - captured
+    }
+}
+
+impl Variant for Custom {
+    fn hole_count(&self) -> usize {
+        self.position.hole_count()
+    }
+
+    fn starting_pieces(&self) -> usize {
+        self.definition.seeds
+    }
+
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn direction(&self) -> Direction {
+        self.definition.direction
+    }
+
+    fn legal_moves(&self, player: Player) -> Vec<Index> {
+        let opponent = self.position.next_player(player);
+        let must_feed = self.definition.must_feed && self.position.row_total(opponent) == 0;
+        let forbid_grand_slam = self.definition.grand_slam == Some(GrandSlam::Forbidden);
+
+        (0..self.position.side_hole_count())
+            .map(|h| Index::Player(player, Hole(h)))
+            .filter(|&hole| self.position.seeds(hole) > 0)
+            .filter(|&hole| {
+                if !must_feed && !forbid_grand_slam {
+                    return true;
+                }
+                let sowing = self.sow(hole, player);
+                let allowed = self.captures_allowed(&sowing);
+                !(sowing.grand_slam && forbid_grand_slam)
+                    && (!must_feed || sowing.remaining(opponent, allowed) > 0)
+            })
+            .collect()
+    }
+
+    fn perform_move(&mut self, index: Index) -> Vec<MoveEvent> {
+        assert!(matches!(index, Index::Player(_, _)));
+        let player = self.position.turn();
+        assert_eq!(
+            index.player(),
+            *player,
+            "Cannot sow from the opponent's side"
+        );
+        self.history.push((index, self.position));
+
+        let sowing = self.sow(index, player);
+        let captures_allowed = self.captures_allowed(&sowing);
+        self.position = sowing.position;
+        let mut events = sowing.events;
+
+        if captures_allowed {
+            let store = Index::Score(player);
+            for hole in sowing.captures {
+                let count = self.position.take(hole);
+                tracing::debug!("Captured {count} stones");
+                *self.position.seeds_mut(store) += count;
+                events.push(MoveEvent::Capture {
+                    from: hole,
+                    to: store,
+                    count,
+                });
+            }
+        } else {
+            tracing::debug!("Grand slam, nothing captured");
+        }
+
+        if !(self.definition.extra_turn && sowing.last == Index::Score(player)) {
+            self.position.set_turn(self.position.next_player(player));
+        }
+        if !self.has_majority() && self.is_over() {
+            events.extend(self.sweep_rows(player));
+        }
+
+        events
+    }
+
+    fn set_position(&mut self, position: Position) -> bool {
+        if !position.same_layout(&self.position) {
+            return false;
+        }
+        self.position = position;
+        self.history.clear();
+        true
+    }
+
+    fn undo_move(&mut self) -> bool {
+        let Some((_, previous)) = self.history.pop() else {
+            return false;
+        };
+        self.position = previous;
+        true
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        self.is_over().then(|| Outcome::from_stores(&self.position))
+    }
+
+    fn moves(&self) -> Vec<Index> {
+        self.history.iter().map(|&(index, _)| index).collect()
+    }
+
+    fn first_player(&self) -> Player {
+        self.history
+            .first()
+            .map_or(self.position.turn(), |(_, start)| start.turn())
+    }
+
+    fn clone_box(&self) -> Box<dyn Variant> {
+        Box::new(self.clone())
+    }
+}
+
+impl Custom {
+    /// A new game following `definition`, or an error if it can't be played.
+    pub fn new(definition: Definition) -> Result<Self, DefinitionError> {
+        definition.validate()?;
+        Ok(Self {
+            // The player nearest the camera moves first.
+            position: Position::with_rows(
+                definition.players,
+                definition.rows,
+                definition.holes,
+                definition.seeds,
+                Player(1),
+            ),
+            definition: Arc::new(definition),
+            history: Vec::new(),
+        })
+    }
+
+    /// The same game, but with `first` making the opening move.
+    pub fn with_first_player(mut self, first: Player) -> Self {
+        self.position.set_turn(first);
+        self
+    }
+
+    /// The rules this game is played by.
+    pub fn definition(&self) -> &Definition {
+        &self.definition
+    }
+
+    /// The hole or store after `index` in sowing order for `mover`.
+    fn step(&self, index: Index, mover: Player) -> Index {
+        let position = &self.position;
+        match (self.definition.circuit, self.definition.stores) {
+            (Circuit::Own, _) => index.next_own_hole(position),
+            (Circuit::Board, Stores::Skip) => index.next_hole(position),
+            (Circuit::Board, Stores::Own) => index.next(mover, position),
+            // Every side leads into its owner's store.
+            (Circuit::Board, Stores::All) => index.next(Player(index.player()), position),
+        }
+    }
+
+    /// Plays out sowing from `origin` for `mover` on a copy of the board.
+    fn sow(&self, origin: Index, mover: Player) -> Sowing {
+        let mut position = self.position;
+        let mut events = Vec::new();
+        let mut from = origin;
+        let mut index = origin;
+        for relays in 0.. {
+            let seeds = position.take(from);
+            events.push(MoveEvent::PickUp { from, count: seeds });
+            for _ in 0..seeds {
+                index = self.step(index, mover);
+                if self.definition.skip_origin && index == from {
+                    index = self.step(index, mover);
+                }
+                *position.seeds_mut(index) += 1;
+                events.push(MoveEvent::Sow { to: index });
+            }
+
+            let relay = self.definition.relay
+                && matches!(index, Index::Player(_, _))
+                && position.seeds(index) > 1
+                && relays < MAX_RELAYS;
+            if !relay {
+                break;
+            }
+            from = index;
+        }
+
+        let captures = self
+            .definition
+            .captures
+            .iter()
+            .map(|capture| Self::captured(capture, &position, index, mover))
+            .find(|captures| !captures.is_empty())
+            .unwrap_or_default();
+        let grand_slam = captures.iter().any(|hole| hole.player() != *mover)
+            && (0..self.player_count())
+                .filter(|&p| p != *mover)
+                .flat_map(|p| {
+                    (0..self.position.side_hole_count())
+                        .map(move |h| Index::Player(Player(p), Hole(h)))
+                })
+                .all(|hole| position.seeds(hole) == 0 || captures.contains(&hole));
+
+        Sowing {
+            position,
+            events,
+            last: index,
+            captures,
+            grand_slam,
+        }
+    }
+
+    /// The holes `capture` takes once the last seed lands in `last`.
+    fn captured(capture: &Capture, position: &Position, last: Index, mover: Player) -> Vec<Index> {
+        let Index::Player(Player(owner), Hole(h)) = last else {
+            return Vec::new();
+        };
+        match capture {
+            Capture::Opposite { row } => {
+                let Some(opposite) = last.opposite_bucket(position) else {
+                    return Vec::new();
+                };
+                if row.includes(mover, owner)
+                    && position.seeds(last) == 1
+                    && position.seeds(opposite) > 0
+                {
+                    vec![last, opposite]
+                } else {
+                    Vec::new()
+                }
+            }
+            Capture::Counts {
+                counts,
+                row,
+                chained,
+            } => {
+                if !row.includes(mover, owner) {
+                    return Vec::new();
+                }
+                let run = (0..=h)
+                    .rev()
+                    .map(|h| Index::Player(Player(owner), Hole(h)))
+                    .take_while(|&hole| counts.contains(&position.seeds(hole)));
+                if *chained {
+                    run.collect()
+                } else {
+                    run.take(1).collect()
+                }
+            }
+        }
+    }
+
+    fn captures_allowed(&self, sowing: &Sowing) -> bool {
+        !sowing.grand_slam || matches!(self.definition.grand_slam, None | Some(GrandSlam::Capture))
+    }
+
+    /// Whether a player has captured more than half of the seeds, if that ends the game.
+    fn has_majority(&self) -> bool {
+        if !self.definition.majority {
+            return false;
+        }
+        let total: usize = (0..self.player_count())
+            .map(|p| self.position.row_total(Player(p)) + self.position.store(Player(p)))
+            .sum();
+        (0..self.player_count()).any(|p| self.position.store(Player(p)) > total / 2)
+    }
+
+    fn is_over(&self) -> bool {
+        self.has_majority()
+            || match self.definition.end {
+                End::RowEmpty => {
+                    (0..self.player_count()).any(|p| self.position.row_total(Player(p)) == 0)
+                }
+                End::NoMoves => self.legal_moves(self.position.turn()).is_empty(),
+            }
+    }
+
+    /// Moves every seed still on the board into a store, once `mover` has ended the game.
+    fn sweep_rows(&mut self, mover: Player) -> Vec<MoveEvent> {
+        let mut events = Vec::new();
+        for p in 0..self.player_count() {
+            let store = match self.definition.sweep {
+                Sweep::ToOwner => Index::Score(Player(p)),
+                Sweep::ToMover => Index::Score(mover),
+            };
+            for h in 0..self.position.side_hole_count() {
+                let hole = Index::Player(Player(p), Hole(h));
+                let count = self.position.take(hole);
+                if count == 0 {
+                    continue;
+                }
+                *self.position.seeds_mut(store) += count;
+                events.push(MoveEvent::Sweep {
+                    from: hole,
+                    to: store,
+                    count,
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A two-player, one-row definition sown around the whole board with nothing else switched
+    /// on, for each test to change what it needs.
+    fn definition(holes: usize, seeds: usize) -> Definition {
+        Definition {
+            name: "Test".to_string(),
+            description: String::new(),
+            players: 2,
+            rows: 1,
+            holes,
+            seeds,
+            direction: Direction::default(),
+            circuit: Circuit::Board,
+            stores: Stores::Skip,
+            skip_origin: false,
+            relay: false,
+            extra_turn: false,
+            captures: Vec::new(),
+            grand_slam: None,
+            must_feed: false,
+            end: End::RowEmpty,
+            majority: false,
+            sweep: Sweep::ToOwner,
+        }
+    }
+
+    /// A game of `definition`, from `position` written in the crate's notation.
+    fn game(definition: Definition, position: &str) -> Custom {
+        let mut game = Custom::new(definition).unwrap();
+        assert!(game.set_position(position.parse().unwrap()));
+        game
+    }
+
+    fn hole(player: usize, hole: usize) -> Index {
+        Index::Player(Player(player), Hole(hole))
+    }
+
+    fn captures(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Capture { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn sweeps(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Sweep { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn pick_ups(events: &[MoveEvent]) -> usize {
+        events
+            .iter()
+            .filter(|event| matches!(event, MoveEvent::PickUp { .. }))
+            .count()
+    }
+
+    /// Plays the opening `a c c` of a seven-hole relay game, which relays from the second move
+    /// on and captures on the third.
+    fn play_relay_opening(text: &str) -> Custom {
+        let mut game = Custom::new(ron::from_str(text).unwrap()).unwrap();
+        assert_eq!(
+            game.position().to_string(),
+            "7,7,7,7,7,7,7[0]/7,7,7,7,7,7,7[0] 1"
+        );
+
+        // Seven seeds from the first hole end in the store, for another turn.
+        let events = game.perform_move(hole(1, 0));
+        assert_eq!(pick_ups(&events), 1);
+        assert_eq!(
+            game.position().to_string(),
+            "7,7,7,7,7,7,7[0]/0,8,8,8,8,8,8[1] 1"
+        );
+
+        let events = game.perform_move(hole(1, 2));
+        assert!(pick_ups(&events) > 1);
+        assert!(captures(&events).is_empty());
+        assert_eq!(
+            game.position().to_string(),
+            "9,9,1,9,9,0,9[0]/2,10,2,1,11,11,11[4] 1"
+        );
+
+        let events = game.perform_move(hole(1, 2));
+        assert_eq!(captures(&events), [(hole(1, 5), 1), (hole(0, 1), 13)]);
+        assert_eq!(
+            game.position().to_string(),
+            "1,0,5,13,2,4,13[0]/6,3,4,6,4,0,15[22] 0"
+        );
+        assert_eq!(game.outcome(), None);
+        game
+    }
+
+    #[test]
+    fn congkak_plays_on_until_the_mover_is_stuck() {
+        let load = |text| {
+            let mut game = Custom::new(ron::from_str(text).unwrap()).unwrap();
+            assert!(game.set_position("1,0,0,0,0,0,0[10]/0,0,0,0,0,0,2[10] 1".parse().unwrap()));
+            game
+        };
+        let mut congkak = load(include_str!("../../../assets/variants/congkak.variant.ron"));
+        let mut dakon = load(include_str!("../../../assets/variants/dakon.variant.ron"));
+
+        // Emptying your own row ends Dakon, but Congkak goes on while the next player can move.
+        dakon.perform_move(hole(1, 6));
+        assert!(dakon.outcome().is_some());
+        congkak.perform_move(hole(1, 6));
+        assert_eq!(
+            congkak.position().to_string(),
+            "0,1,1,0,0,0,0[10]/0,0,0,0,0,0,0[11] 0"
+        );
+        assert_eq!(congkak.outcome(), None);
+
+        // Once the player to move is left with nothing, the seeds go to whoever's side they're on.
+        let events = congkak.perform_move(hole(0, 2));
+        assert!(captures(&events).is_empty());
+        assert_eq!(sweeps(&events), [(hole(0, 1), 1), (hole(0, 3), 1)]);
+        assert_eq!(
+            congkak.position().to_string(),
+            "0,0,0,0,0,0,0[12]/0,0,0,0,0,0,0[11] 1"
+        );
+        assert_eq!(congkak.outcome(), Some(Outcome::Winner(Player(0))));
+    }
+
+    #[test]
+    fn dakon_plays_a_known_line() {
+        let mut game =
+            play_relay_opening(include_str!("../../../assets/variants/dakon.variant.ron"));
+        assert_eq!(game.direction(), Direction::Counterclockwise);
+        for _ in 0..3 {
+            assert!(game.undo_move());
+        }
+        assert_eq!(
+            game.position().to_string(),
+            "7,7,7,7,7,7,7[0]/7,7,7,7,7,7,7[0] 1"
+        );
+        assert!(!game.undo_move());
+    }
+
+    #[test]
+    fn counting_captures_chain_backwards() {
+        let capture = |chained| Capture::Counts {
+            counts: vec![2, 3],
+            row: Whose::Opponent,
+            chained,
+        };
+        let position = "1,0,0,0,0,3[0]/1,2,1,5,1,1[0] 0";
+
+        let mut chained = definition(6, 4);
+        chained.captures = vec![capture(true)];
+        let mut chained = game(chained, position);
+        let events = chained.perform_move(hole(0, 5));
+        assert_eq!(
+            captures(&events),
+            [(hole(1, 2), 2), (hole(1, 1), 3), (hole(1, 0), 2)]
+        );
+        assert_eq!(chained.position().store(Player(0)), 7);
+
+        let mut single = definition(6, 4);
+        single.captures = vec![capture(false)];
+        let mut single = game(single, position);
+        let events = single.perform_move(hole(0, 5));
+        assert_eq!(captures(&events), [(hole(1, 2), 2)]);
+        assert_eq!(single.position().store(Player(0)), 2);
+    }
+
+    #[test]
+    fn own_circuit_stays_on_the_movers_rows() {
+        let mut definition = definition(3, 2);
+        definition.rows = 2;
+        definition.circuit = Circuit::Own;
+        let mut game = game(definition, "0,0,0|0,0,3[0]/1,1,1|1,1,1[0] 0");
+
+        let events = game.perform_move(hole(0, 5));
+        assert_eq!(
+            events,
+            [
+                MoveEvent::PickUp {
+                    from: hole(0, 5),
+                    count: 3,
+                },
+                MoveEvent::Sow { to: hole(0, 0) },
+                MoveEvent::Sow { to: hole(0, 1) },
+                MoveEvent::Sow { to: hole(0, 2) },
+            ]
+        );
+        assert_eq!(
+            game.position().to_string(),
+            "1,1,1|0,0,0[0]/1,1,1|1,1,1[0] 1"
+        );
+    }
+
+    #[test]
+    fn endless_relays_are_cut_short() {
+        // Sowing round a single hole of its own ends where it started, every time.
+        let mut definition = definition(1, 2);
+        definition.circuit = Circuit::Own;
+        definition.relay = true;
+        let mut game = Custom::new(definition)
+            .unwrap()
+            .with_first_player(Player(0));
+
+        let events = game.perform_move(hole(0, 0));
+        assert_eq!(pick_ups(&events), MAX_RELAYS + 1);
+        assert_eq!(game.seeds(hole(0, 0)), 2);
+    }
+
+    #[test]
+    fn bad_definitions_are_rejected() {
+        let with = |change: fn(&mut Definition)| {
+            let mut definition = definition(6, 4);
+            change(&mut definition);
+            definition
+        };
+        let cases = [
+            (
+                with(|d| d.players = 1),
+                DefinitionError::InvalidPlayerCount(1),
+            ),
+            (
+                with(|d| d.players = MAX_PLAYER_COUNT + 1),
+                DefinitionError::InvalidPlayerCount(MAX_PLAYER_COUNT + 1),
+            ),
+            (with(|d| d.rows = 0), DefinitionError::InvalidRowCount(0)),
+            (
+                with(|d| d.rows = MAX_ROW_COUNT + 1),
+                DefinitionError::InvalidRowCount(MAX_ROW_COUNT + 1),
+            ),
+            (with(|d| d.holes = 0), DefinitionError::InvalidHoleCount(0)),
+            (
+                with(|d| d.holes = MAX_HOLE_COUNT + 1),
+                DefinitionError::InvalidHoleCount(MAX_HOLE_COUNT + 1),
+            ),
+            (with(|d| d.seeds = 0), DefinitionError::NoSeeds),
+            (
+                with(|d| {
+                    d.captures = vec![Capture::Counts {
+                        counts: Vec::new(),
+                        row: Whose::Either,
+                        chained: false,
+                    }];
+                }),
+                DefinitionError::InvalidCounts(Vec::new()),
+            ),
+            (
+                with(|d| {
+                    d.captures = vec![
+                        Capture::Opposite { row: Whose::Own },
+                        Capture::Counts {
+                            counts: vec![0, 4],
+                            row: Whose::Either,
+                            chained: true,
+                        },
+                    ];
+                }),
+                DefinitionError::InvalidCounts(vec![0, 4]),
+            ),
+        ];
+        for (definition, error) in cases {
+            assert_eq!(definition.validate(), Err(error.clone()));
+            assert_eq!(Custom::new(definition).unwrap_err(), error);
+        }
+        assert_eq!(definition(6, 4).validate(), Ok(()));
+    }
+}
@@ -0,0 +1,352 @@
+//! Kalah, the mancala game most commonly sold in toy shops.
+//!
+//! Kalah is played with anywhere from four to eight holes a side, and three to six seeds in every
+//! hole; tournaments write this as Kalah(holes, seeds), and usually play Kalah(6, 6).
+
+use core::ops::RangeInclusive;
+
+use super::{Index, MoveEvent, Outcome, Variant};
+use crate::{Hole, Player, Position, HOLE_COUNT};
+
+/// The number of seeds in every hole at the start of the game, unless another count is chosen.
+pub const STARTING_PIECES: usize = 4;
+
+/// The number of holes a side Kalah may be played with.
+pub const HOLE_COUNTS: RangeInclusive<usize> = 4..=8;
+
+/// The number of seeds a hole Kalah may be started with.
+pub const SEED_COUNTS: RangeInclusive<usize> = 3..=6;
+
+/// Whose store the seeds left on the board go to, once one side has run out.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Sweep {
+    /// Every player keeps the seeds left on their own row. This is the standard rule.
+    #[default]
+    ToOwner,
+    /// The player who made the last move takes every seed left on the board.
+    ToMover,
+}
+
+/// Kalah, where the last seed landing in your store earns another turn, and landing in an empty
+/// hole of your own captures the seeds opposite.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct Kalah {
+    position: Position,
+    /// Every move played so far, along with the position it was played from.
+    history: Vec<(Index, Position)>,
+    starting_pieces: usize,
+    sweep: Sweep,
+}
+
+impl Default for Kalah {
+    fn default() -> Self {
+        Self::new(HOLE_COUNT, STARTING_PIECES, Sweep::default())
+    }
+}
+
+impl Variant for Kalah {
+    fn hole_count(&self) -> usize {
+        self.position.hole_count()
+    }
+
+    fn starting_pieces(&self) -> usize {
+        self.starting_pieces
+    }
+
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn legal_moves(&self, player: Player) -> Vec<Index> {
+        (0..self.hole_count())
+            .map(|h| Index::Player(player, Hole(h)))
+            .filter(|&hole| self.position.seeds(hole) > 0)
+            .collect()
+    }
+
+    fn perform_move(&mut self, mut index: Index) -> Vec<MoveEvent> {
+        assert!(matches!(index, Index::Player(_, _)));
+        let player = self.position.turn();
+        assert_eq!(
+            index.player(),
+            *player,
+            "Cannot sow from the opponent's side"
+        );
+        self.history.push((index, self.position));
+
+        let seeds = self.position.take(index);
+        let mut events = vec![MoveEvent::PickUp {
+            from: index,
+            count: seeds,
+        }];
+
+        for _ in 0..seeds {
+            index = index.next(player, &self.position);
+            *self.position.seeds_mut(index) += 1;
+            events.push(MoveEvent::Sow { to: index });
+        }
+
+        if !matches!(index, Index::Score(_)) {
+            if let Some(opposite) = index.opposite_bucket(&self.position) {
+                // If the opposite bucket contains a stone, and the current bucket was empty, AND
+                // the bucket is on the current player's side; capture the stones in both buckets.
+                if index.player() == *player
+                    && self.position.seeds(index) == 1
+                    && self.position.seeds(opposite) != 0
+                {
+                    let score_index = Index::Score(player);
+                    let ours = self.position.take(index);
+                    let theirs = self.position.take(opposite);
+                    tracing::debug!("Captured {} stones", ours + theirs);
+                    *self.position.seeds_mut(score_index) += ours + theirs;
+                    events.push(MoveEvent::Capture {
+                        from: index,
+                        to: score_index,
+                        count: ours,
+                    });
+                    events.push(MoveEvent::Capture {
+                        from: opposite,
+                        to: score_index,
+                        count: theirs,
+                    });
+                }
+            }
+            self.position.set_turn(self.position.next_player(player));
+        }
+
+        if (0..self.position.player_count()).any(|p| self.position.row_total(Player(p)) == 0) {
+            events.extend(self.sweep_rows(player));
+        }
+
+        events
+    }
+
+    fn set_position(&mut self, position: Position) -> bool {
+        if !position.same_layout(&self.position) {
+            return false;
+        }
+        self.position = position;
+        self.history.clear();
+        true
+    }
+
+    fn undo_move(&mut self) -> bool {
+        let Some((_, previous)) = self.history.pop() else {
+            return false;
+        };
+        self.position = previous;
+        true
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        if (0..self.position.player_count()).all(|p| self.position.row_total(Player(p)) > 0) {
+            return None;
+        }
+
+        Some(Outcome::from_stores(&self.position))
+    }
+
+    fn moves(&self) -> Vec<Index> {
+        self.history.iter().map(|&(index, _)| index).collect()
+    }
+
+    fn first_player(&self) -> Player {
+        self.history
+            .first()
+            .map_or(self.position.turn(), |(_, start)| start.turn())
+    }
+
+    fn clone_box(&self) -> Box<dyn Variant> {
+        Box::new(self.clone())
+    }
+}
+
+impl Kalah {
+    /// A new game of Kalah(`holes`, `seeds`), sweeping the leftover seeds with the given rule.
+    pub fn new(holes: usize, seeds: usize, sweep: Sweep) -> Self {
+        assert!(HOLE_COUNTS.contains(&holes), "Kalah needs 4 to 8 holes");
+        assert!(SEED_COUNTS.contains(&seeds), "Kalah needs 3 to 6 seeds");
+        Self {
+            // The player nearest the camera moves first.
+            position: Position::new(holes, seeds, Player(1)),
+            history: Vec::new(),
+            starting_pieces: seeds,
+            sweep,
+        }
+    }
+
+    /// The same game, but with `first` making the opening move.
+    pub fn with_first_player(mut self, first: Player) -> Self {
+        self.position.set_turn(first);
+        self
+    }
+
+    /// The rule for sweeping up the seeds left on the board at the end of the game.
+    pub fn sweep(&self) -> Sweep {
+        self.sweep
+    }
+
+    /// Moves every seed still on the board into a store, once `mover` has ended the game.
+    fn sweep_rows(&mut self, mover: Player) -> Vec<MoveEvent> {
+        let mut events = Vec::new();
+        for p in 0..self.position.player_count() {
+            let store = match self.sweep {
+                Sweep::ToOwner => Index::Score(Player(p)),
+                Sweep::ToMover => Index::Score(mover),
+            };
+            for h in 0..self.hole_count() {
+                let hole = Index::Player(Player(p), Hole(h));
+                let count = self.position.take(hole);
+                if count == 0 {
+                    continue;
+                }
+                *self.position.seeds_mut(store) += count;
+                events.push(MoveEvent::Sweep {
+                    from: hole,
+                    to: store,
+                    count,
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game of Kalah with `holes` a side from `position`, written in the crate's notation.
+    fn game(holes: usize, position: &str, sweep: Sweep) -> Kalah {
+        let mut game = Kalah::new(holes, STARTING_PIECES, sweep);
+        assert!(game.set_position(position.parse().unwrap()));
+        game
+    }
+
+    fn hole(player: usize, hole: usize) -> Index {
+        Index::Player(Player(player), Hole(hole))
+    }
+
+    fn captures(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Capture { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn sweeps(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Sweep { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn sown(events: &[MoveEvent]) -> Vec<Index> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Sow { to } => Some(to),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sowing_passes_the_own_store_but_skips_the_opponents() {
+        let mut game = game(4, "1,1,1,6[0]/1,1,1,1[0] 0", Sweep::default());
+        let events = game.perform_move(hole(0, 3));
+
+        assert_eq!(
+            sown(&events),
+            [
+                Index::Score(Player(0)),
+                hole(1, 0),
+                hole(1, 1),
+                hole(1, 2),
+                hole(1, 3),
+                hole(0, 0),
+            ]
+        );
+        assert_eq!(game.position().to_string(), "2,1,1,0[1]/2,2,2,2[0] 1");
+    }
+
+    #[test]
+    fn sowing_laps_a_board_of_eight_holes() {
+        let start = "1,1,1,1,1,1,1,10[0]/1,1,1,1,1,1,1,1[0] 0";
+        let mut game = game(8, start, Sweep::default());
+        let events = game.perform_move(hole(0, 7));
+
+        assert!(!sown(&events).contains(&Index::Score(Player(1))));
+        assert_eq!(sown(&events).last(), Some(&hole(0, 0)));
+        assert_eq!(
+            game.position().to_string(),
+            "2,1,1,1,1,1,1,0[1]/2,2,2,2,2,2,2,2[0] 1"
+        );
+    }
+
+    #[test]
+    fn last_seed_in_the_store_earns_another_turn() {
+        let mut game = game(6, "1,0,0,0,0,1[0]/1,1,1,1,1,1[0] 0", Sweep::default());
+        game.perform_move(hole(0, 5));
+
+        assert_eq!(game.current_player(), Player(0));
+        assert_eq!(
+            game.position().to_string(),
+            "1,0,0,0,0,0[1]/1,1,1,1,1,1[0] 0"
+        );
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn last_seed_in_an_empty_own_hole_captures_the_opposite_hole() {
+        let mut game = game(6, "1,0,0,0,0,1[0]/1,1,1,1,3,1[0] 0", Sweep::default());
+        let events = game.perform_move(hole(0, 0));
+
+        assert_eq!(captures(&events), [(hole(0, 1), 1), (hole(1, 4), 3)]);
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,1[4]/1,1,1,1,0,1[0] 1"
+        );
+    }
+
+    /// Moving the last seed from `F` into the store leaves player 0 with an empty row.
+    const LAST_MOVE: &str = "0,0,0,0,0,1[10]/1,0,0,0,0,2[9] 0";
+
+    #[test]
+    fn sweep_to_owner_leaves_each_row_to_its_player() {
+        let mut game = game(6, LAST_MOVE, Sweep::ToOwner);
+        let events = game.perform_move(hole(0, 5));
+
+        assert!(captures(&events).is_empty());
+        assert_eq!(sweeps(&events), [(hole(1, 0), 1), (hole(1, 5), 2)]);
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,0[11]/0,0,0,0,0,0[12] 0"
+        );
+        assert_eq!(game.outcome(), Some(Outcome::Winner(Player(1))));
+    }
+
+    #[test]
+    fn sweep_to_mover_gives_every_row_to_the_last_mover() {
+        let mut game = game(6, LAST_MOVE, Sweep::ToMover);
+        let events = game.perform_move(hole(0, 5));
+
+        assert_eq!(sweeps(&events), [(hole(1, 0), 1), (hole(1, 5), 2)]);
+        assert!(events.iter().all(|event| match *event {
+            MoveEvent::Sweep { to, .. } => to == Index::Score(Player(0)),
+            _ => true,
+        }));
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,0[14]/0,0,0,0,0,0[9] 0"
+        );
+        assert_eq!(game.outcome(), Some(Outcome::Winner(Player(0))));
+    }
+}
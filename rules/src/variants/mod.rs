@@ -0,0 +1,377 @@
+//! The mancala variants, and the [`Variant`] trait they all implement.
+
+use core::fmt::Debug;
+
+use crate::{Hole, Player, Position};
+
+pub mod custom;
+pub mod kalah;
+pub mod oware;
+
+/// A hole or store on the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Index {
+    /// One of a player's holes.
+    Player(Player, Hole),
+    /// A player's store.
+    Score(Player),
+}
+
+impl Index {
+    /// The next hole or store in sowing order for `start`, passing through every player's holes
+    /// but only `start`'s store.
+    fn next(self, Player(start): Player, position: &Position) -> Self {
+        match self {
+            Index::Player(Player(p), Hole(h)) => {
+                if h + 1 >= position.side_hole_count() {
+                    if p == start {
+                        Index::Score(Player(p))
+                    } else {
+                        Index::Player(position.next_player(Player(p)), Hole(0))
+                    }
+                } else {
+                    Index::Player(Player(p), Hole(h + 1))
+                }
+            }
+            Index::Score(player) => Index::Player(position.next_player(player), Hole(0)),
+        }
+    }
+
+    /// The next hole in sowing order, skipping over every store.
+    fn next_hole(self, position: &Position) -> Self {
+        match self {
+            Index::Player(Player(p), Hole(h)) if h + 1 < position.side_hole_count() => {
+                Index::Player(Player(p), Hole(h + 1))
+            }
+            Index::Player(player, _) | Index::Score(player) => {
+                Index::Player(position.next_player(player), Hole(0))
+            }
+        }
+    }
+
+    /// The next hole in sowing order around its owner's own rows, as four-row boards are sown.
+    fn next_own_hole(self, position: &Position) -> Self {
+        match self {
+            Index::Player(player, Hole(h)) => {
+                Index::Player(player, Hole((h + 1) % position.side_hole_count()))
+            }
+            Index::Score(player) => Index::Player(player, Hole(0)),
+        }
+    }
+
+    /// The hole facing this one across the middle of a two-player board.
+    ///
+    /// Only holes in the row nearest the middle have one, and only when there are two players.
+    fn opposite_bucket(&self, position: &Position) -> Option<Self> {
+        let Index::Player(player, Hole(h)) = *self else {
+            return None;
+        };
+        let holes = position.hole_count();
+        // The last row sown is the one nearest the middle.
+        let front = (position.row_count() - 1) * holes;
+        if position.player_count() != 2 || h < front {
+            return None;
+        }
+        // The hole that is opposite the current hole has a different index than ours.
+        Some(Index::Player(
+            position.next_player(player),
+            Hole(2 * front + holes - 1 - h),
+        ))
+    }
+
+    fn player(&self) -> usize {
+        match self {
+            Index::Player(Player(p), _) | Index::Score(Player(p)) => *p,
+        }
+    }
+
+    /// The hole this index refers to, or `None` for a store.
+    pub fn hole(&self) -> Option<Hole> {
+        match self {
+            Index::Player(_, h) => Some(*h),
+            Index::Score(_) => None,
+        }
+    }
+}
+
+/// Which way round the board the seeds are sown, as laid out by the game.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    /// The way most of the family is played, and the way the board is laid out.
+    #[default]
+    Counterclockwise,
+    /// The other way round, as in Congkak. The board is mirrored end to end.
+    Clockwise,
+}
+
+/// Something that happened to the seeds while a move was performed.
+///
+/// The events are listed in the order they happened, so replaying them one by one moves every
+/// seed from where it was to where the rules put it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoveEvent {
+    /// Every seed in `from` was lifted into the mover's hand.
+    PickUp {
+        /// The hole the seeds were lifted from.
+        from: Index,
+        /// How many seeds were lifted.
+        count: usize,
+    },
+    /// A single seed from the mover's hand was dropped into `to`.
+    Sow {
+        /// Where the seed was dropped.
+        to: Index,
+    },
+    /// Every seed in `from` was moved into the store `to`.
+    Capture {
+        /// The hole that was emptied.
+        from: Index,
+        /// The store the seeds went to.
+        to: Index,
+        /// How many seeds were moved.
+        count: usize,
+    },
+    /// Every seed left in `from` was moved into the store `to` once the game was over, without
+    /// having been captured.
+    Sweep {
+        /// The hole that was emptied.
+        from: Index,
+        /// The store the seeds went to.
+        to: Index,
+        /// How many seeds were moved.
+        count: usize,
+    },
+}
+
+/// How a finished game turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "bevy", derive(bevy_reflect::Reflect))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    /// The player won outright.
+    Winner(Player),
+    /// The stores ended level.
+    Draw,
+}
+
+impl Outcome {
+    /// Whoever has the most seeds in their store wins, or it's a draw if the top stores are level.
+    pub fn from_stores(position: &Position) -> Self {
+        let players = 0..position.player_count();
+        let best = players
+            .clone()
+            .map(|p| position.store(Player(p)))
+            .max()
+            .unwrap_or_default();
+        let mut leaders = players.filter(|&p| position.store(Player(p)) == best);
+        match (leaders.next(), leaders.next()) {
+            (Some(winner), None) => Outcome::Winner(Player(winner)),
+            _ => Outcome::Draw,
+        }
+    }
+
+    /// How `player` did, on a scale from `0.0` for a loss to `1.0` for a win.
+    pub fn reward(&self, player: Player) -> f64 {
+        match self {
+            Outcome::Winner(winner) if *winner == player => 1.0,
+            Outcome::Winner(_) => 0.0,
+            Outcome::Draw => 0.5,
+        }
+    }
+}
+
+/// The rules of a single mancala game, along with the current state of its board.
+pub trait Variant: Send + Sync + Debug {
+    /// The number of holes in each of a player's rows.
+    fn hole_count(&self) -> usize;
+
+    /// The number of rows of holes each player has.
+    fn row_count(&self) -> usize {
+        self.position().row_count()
+    }
+
+    /// The number of players around the board.
+    fn player_count(&self) -> usize {
+        self.position().player_count()
+    }
+
+    /// The number of seeds in every hole at the start of the game.
+    fn starting_pieces(&self) -> usize;
+
+    /// The current state of the board.
+    fn position(&self) -> &Position;
+
+    /// Which way round the board the seeds are sown.
+    fn direction(&self) -> Direction {
+        Direction::Counterclockwise
+    }
+
+    /// The number of seeds in the hole or store at `index`.
+    fn seeds(&self, index: Index) -> usize {
+        self.position().seeds(index)
+    }
+
+    /// The player whose turn it is.
+    fn current_player(&self) -> Player {
+        self.position().turn()
+    }
+
+    /// Every hole `player` is allowed to sow from in the current position.
+    fn legal_moves(&self, player: Player) -> Vec<Index>;
+
+    /// Whether `player` is allowed to sow from `index` in the current position.
+    fn is_legal_move(&self, index: Index, player: Player) -> bool {
+        self.legal_moves(player).contains(&index)
+    }
+
+    /// Replaces the board with `position`, forgetting the move history.
+    ///
+    /// Returns `false`, leaving the board alone, if `position` doesn't fit this variant's board.
+    fn set_position(&mut self, position: Position) -> bool;
+
+    /// Sows from `index` for the current player, returning everything that happened.
+    fn perform_move(&mut self, index: Index) -> Vec<MoveEvent>;
+
+    /// Takes back the last move, returning `false` if there was nothing to take back.
+    fn undo_move(&mut self) -> bool;
+
+    /// Every move played so far, oldest first.
+    fn moves(&self) -> Vec<Index>;
+
+    /// The player who made, or will make, the opening move.
+    fn first_player(&self) -> Player;
+
+    /// How the game turned out, once it is over.
+    fn outcome(&self) -> Option<Outcome>;
+
+    /// A copy of this variant, including its position and move history.
+    fn clone_box(&self) -> Box<dyn Variant>;
+}
+
+impl Clone for Box<dyn Variant> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The variant that will be played when the next game starts.
+#[derive(Debug)]
+#[cfg_attr(feature = "bevy", derive(bevy_ecs::system::Resource))]
+pub enum ChosenVariant {
+    /// The Kalah variant. Simple, considered a childs game.
+    Kalah(kalah::Kalah),
+    /// The Oware (Abapa) variant. The most widely played mancala game.
+    Oware(oware::Oware),
+    /// A variant read from a [`custom::Definition`].
+    Custom(custom::Custom),
+}
+
+impl Default for ChosenVariant {
+    fn default() -> Self {
+        Self::Kalah(kalah::Kalah::default())
+    }
+}
+
+impl ChosenVariant {
+    /// Every variant, each with its standard rules.
+    pub fn all() -> Vec<Self> {
+        vec![
+            Self::Kalah(kalah::Kalah::default()),
+            Self::Oware(oware::Oware::default()),
+        ]
+    }
+
+    /// The name the variant is known by.
+    pub fn name(&self) -> &str {
+        match self {
+            ChosenVariant::Kalah(_) => "Kalah",
+            ChosenVariant::Oware(_) => "Oware",
+            ChosenVariant::Custom(v) => &v.definition().name,
+        }
+    }
+
+    /// A sentence or two on how the variant is played.
+    pub fn description(&self) -> &str {
+        match self {
+            ChosenVariant::Kalah(_) => {
+                "Sow into your own store as you pass it. Ending in your store earns another \
+                 turn, and ending in an empty hole of your own captures the seeds opposite."
+            }
+            ChosenVariant::Oware(_) => {
+                "Stores are skipped while sowing. Ending on your opponent's side with two or \
+                 three seeds captures them, along with any twos and threes just before."
+            }
+            ChosenVariant::Custom(v) => &v.definition().description,
+        }
+    }
+
+    /// Whether this is the same variant as `other`, whatever rules each is played with.
+    pub fn same_variant(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Each definition is a variant of its own.
+            (ChosenVariant::Custom(a), ChosenVariant::Custom(b)) => {
+                a.definition().name == b.definition().name
+            }
+            _ => core::mem::discriminant(self) == core::mem::discriminant(other),
+        }
+    }
+
+    /// The player who makes the opening move.
+    pub fn first_player(&self) -> Player {
+        match self {
+            ChosenVariant::Kalah(v) => v.first_player(),
+            ChosenVariant::Oware(v) => v.first_player(),
+            ChosenVariant::Custom(v) => v.first_player(),
+        }
+    }
+
+    /// Has `first` make the opening move.
+    pub fn set_first_player(&mut self, first: Player) {
+        match self {
+            ChosenVariant::Kalah(v) => *v = v.clone().with_first_player(first),
+            ChosenVariant::Oware(v) => *v = v.clone().with_first_player(first),
+            ChosenVariant::Custom(v) => *v = v.clone().with_first_player(first),
+        }
+    }
+
+    /// How many players take part.
+    pub fn player_count(&self) -> usize {
+        match self {
+            ChosenVariant::Kalah(v) => v.player_count(),
+            ChosenVariant::Oware(v) => v.player_count(),
+            ChosenVariant::Custom(v) => v.player_count(),
+        }
+    }
+
+    /// The position the game starts from.
+    pub fn position(&self) -> &Position {
+        match self {
+            ChosenVariant::Kalah(v) => v.position(),
+            ChosenVariant::Oware(v) => v.position(),
+            ChosenVariant::Custom(v) => v.position(),
+        }
+    }
+
+    /// Starts the game from `position` instead of the opening one, such as a puzzle or a position
+    /// shared from another game.
+    ///
+    /// Returns `false`, leaving the variant alone, if `position` doesn't fit its board.
+    pub fn set_position(&mut self, position: Position) -> bool {
+        match self {
+            ChosenVariant::Kalah(v) => v.set_position(position),
+            ChosenVariant::Oware(v) => v.set_position(position),
+            ChosenVariant::Custom(v) => v.set_position(position),
+        }
+    }
+
+    /// A fresh copy of the chosen variant, ready to play.
+    pub fn to_variant(&self) -> Box<dyn Variant> {
+        match self {
+            ChosenVariant::Kalah(v) => Box::new(v.clone()),
+            ChosenVariant::Oware(v) => Box::new(v.clone()),
+            ChosenVariant::Custom(v) => Box::new(v.clone()),
+        }
+    }
+}
@@ -0,0 +1,479 @@
+//! Oware under the Abapa rules, played across West Africa and the Caribbean.
+//!
+//! Over the board, a game that goes round in circles is ended by agreement, with each player
+//! keeping the seeds on their own row. Here the game ends the same way as soon as a position
+//! comes round again with the same player to move.
+
+use super::{Index, MoveEvent, Outcome, Variant};
+use crate::{Hole, Player, Position, HOLE_COUNT};
+
+/// The number of seeds in every hole at the start of the game.
+pub const STARTING_PIECES: usize = 4;
+
+/// What happens when a move would capture every seed on the opponent's row.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GrandSlam {
+    /// The move may be played, but captures nothing. This is the Abapa rule.
+    #[default]
+    NoCapture,
+    /// The move captures as normal, leaving the opponent with nothing.
+    Capture,
+    /// The move may not be played at all.
+    Forbidden,
+}
+
+/// Oware, where seeds are captured from the opponent's row in groups of two and three, and
+/// players must always leave their opponent something to play.
+#[derive(Debug, Hash, PartialEq, Eq, Clone)]
+pub struct Oware {
+    position: Position,
+    /// Every move played so far, along with the position it was played from.
+    history: Vec<(Index, Position)>,
+    grand_slam: GrandSlam,
+}
+
+impl Default for Oware {
+    fn default() -> Self {
+        Self::with_grand_slam(GrandSlam::default())
+    }
+}
+
+/// The result of sowing from a single hole, before anything is captured.
+struct Sowing {
+    /// The board once every seed has been sown.
+    position: Position,
+    /// The hole each seed lands in, in sowing order.
+    path: Vec<Index>,
+    /// The opponent's holes that would be captured, starting from the last seed.
+    captures: Vec<Index>,
+    /// Whether the captures would take every seed from the opponent's row.
+    grand_slam: bool,
+}
+
+impl Sowing {
+    /// The number of seeds left on `player`'s row once the move is complete.
+    fn remaining(&self, player: Player, grand_slam: GrandSlam) -> usize {
+        let captured = if self.captures_allowed(grand_slam) {
+            self.captures
+                .iter()
+                .filter(|index| index.player() == *player)
+                .map(|&index| self.position.seeds(index))
+                .sum()
+        } else {
+            0
+        };
+        self.position.row_total(player) - captured
+    }
+
+    fn captures_allowed(&self, grand_slam: GrandSlam) -> bool {
+        !self.grand_slam || grand_slam == GrandSlam::Capture
+    }
+}
+
+impl Variant for Oware {
+    fn hole_count(&self) -> usize {
+        HOLE_COUNT
+    }
+
+    fn starting_pieces(&self) -> usize {
+        STARTING_PIECES
+    }
+
+    fn position(&self) -> &Position {
+        &self.position
+    }
+
+    fn legal_moves(&self, player: Player) -> Vec<Index> {
+        // A player must leave their opponent something to play with, if they are able to.
+        let opponent = self.position.next_player(player);
+        let must_feed = self.position.row_total(opponent) == 0;
+
+        (0..HOLE_COUNT)
+            .map(|h| Index::Player(player, Hole(h)))
+            .filter(|&hole| self.position.seeds(hole) > 0)
+            .filter(|&hole| {
+                let sowing = self.sow(hole);
+                !(sowing.grand_slam && self.grand_slam == GrandSlam::Forbidden)
+                    && (!must_feed || sowing.remaining(opponent, self.grand_slam) > 0)
+            })
+            .collect()
+    }
+
+    fn perform_move(&mut self, index: Index) -> Vec<MoveEvent> {
+        assert!(matches!(index, Index::Player(_, _)));
+        let player = self.position.turn();
+        assert_eq!(
+            index.player(),
+            *player,
+            "Cannot sow from the opponent's side"
+        );
+        self.history.push((index, self.position));
+
+        let sowing = self.sow(index);
+        let mut events = vec![MoveEvent::PickUp {
+            from: index,
+            count: sowing.path.len(),
+        }];
+        events.extend(sowing.path.iter().map(|&to| MoveEvent::Sow { to }));
+        self.position = sowing.position;
+
+        if sowing.captures_allowed(self.grand_slam) {
+            let store = Index::Score(player);
+            for hole in sowing.captures {
+                let count = self.position.take(hole);
+                tracing::debug!("Captured {count} stones");
+                *self.position.seeds_mut(store) += count;
+                events.push(MoveEvent::Capture {
+                    from: hole,
+                    to: store,
+                    count,
+                });
+            }
+        } else {
+            tracing::debug!("Grand slam, nothing captured");
+        }
+
+        let next = self.position.next_player(player);
+        self.position.set_turn(next);
+        if !self.has_majority() && (!self.has_legal_move(next) || self.is_repeated()) {
+            // Nobody can move any more, or the game would go round forever, so every player keeps
+            // the seeds left on their row.
+            events.extend(self.sweep_rows());
+        }
+
+        events
+    }
+
+    fn set_position(&mut self, position: Position) -> bool {
+        if !position.same_layout(&self.position) {
+            return false;
+        }
+        self.position = position;
+        self.history.clear();
+        true
+    }
+
+    fn undo_move(&mut self) -> bool {
+        let Some((_, previous)) = self.history.pop() else {
+            return false;
+        };
+        self.position = previous;
+        true
+    }
+
+    fn outcome(&self) -> Option<Outcome> {
+        if !self.has_majority() && self.has_legal_move(self.position.turn()) {
+            return None;
+        }
+
+        Some(Outcome::from_stores(&self.position))
+    }
+
+    fn moves(&self) -> Vec<Index> {
+        self.history.iter().map(|&(index, _)| index).collect()
+    }
+
+    fn first_player(&self) -> Player {
+        self.history
+            .first()
+            .map_or(self.position.turn(), |(_, start)| start.turn())
+    }
+
+    fn clone_box(&self) -> Box<dyn Variant> {
+        Box::new(self.clone())
+    }
+}
+
+impl Oware {
+    /// A new game of Oware, handling grand slams with the given rule.
+    pub fn with_grand_slam(grand_slam: GrandSlam) -> Self {
+        Self {
+            // The player nearest the camera moves first.
+            position: Position::new(HOLE_COUNT, STARTING_PIECES, Player(1)),
+            history: Vec::new(),
+            grand_slam,
+        }
+    }
+
+    /// The same game, but with `first` making the opening move.
+    pub fn with_first_player(mut self, first: Player) -> Self {
+        self.position.set_turn(first);
+        self
+    }
+
+    /// The rule for moves that would capture every seed on the opponent's row.
+    pub fn grand_slam(&self) -> GrandSlam {
+        self.grand_slam
+    }
+
+    /// Whether a player has captured more than half of the seeds in play, ending the game.
+    fn has_majority(&self) -> bool {
+        let total: usize = (0..self.position.player_count())
+            .map(|p| self.position.row_total(Player(p)) + self.position.store(Player(p)))
+            .sum();
+        (0..self.position.player_count()).any(|p| self.position.store(Player(p)) > total / 2)
+    }
+
+    fn has_legal_move(&self, player: Player) -> bool {
+        !self.legal_moves(player).is_empty()
+    }
+
+    /// Whether the board is back in a position it was played from earlier in the game.
+    fn is_repeated(&self) -> bool {
+        self.history
+            .iter()
+            .any(|(_, previous)| *previous == self.position)
+    }
+
+    /// Plays out sowing from `origin` on a copy of the board.
+    fn sow(&self, origin: Index) -> Sowing {
+        let Index::Player(Player(start), _) = origin else {
+            panic!("Cannot sow from a store");
+        };
+
+        let mut position = self.position;
+        let seeds = position.take(origin);
+        let mut path = Vec::with_capacity(seeds);
+        let mut index = origin;
+        for _ in 0..seeds {
+            index = index.next_hole(&self.position);
+            // With 12 or more seeds the sowing laps the board, but never refills the origin.
+            if index == origin {
+                index = index.next_hole(&self.position);
+            }
+            *position.seeds_mut(index) += 1;
+            path.push(index);
+        }
+
+        // Captures run backwards from the last seed, along the opponent's row only.
+        let mut captures = Vec::new();
+        if let Index::Player(Player(p), Hole(last)) = index {
+            if p != start {
+                captures.extend(
+                    (0..=last)
+                        .rev()
+                        .map(|h| Index::Player(Player(p), Hole(h)))
+                        .take_while(|&hole| (2..=3).contains(&position.seeds(hole))),
+                );
+            }
+        }
+        let opponent = self.position.next_player(Player(start));
+        let grand_slam = !captures.is_empty()
+            && (0..HOLE_COUNT)
+                .map(|h| Index::Player(opponent, Hole(h)))
+                .all(|hole| position.seeds(hole) == 0 || captures.contains(&hole));
+
+        Sowing {
+            position,
+            path,
+            captures,
+            grand_slam,
+        }
+    }
+
+    /// Moves every seed still on the board into the store of the row's owner.
+    fn sweep_rows(&mut self) -> Vec<MoveEvent> {
+        let mut events = Vec::new();
+        for p in 0..self.position.player_count() {
+            let store = Index::Score(Player(p));
+            for h in 0..HOLE_COUNT {
+                let hole = Index::Player(Player(p), Hole(h));
+                let count = self.position.take(hole);
+                if count == 0 {
+                    continue;
+                }
+                *self.position.seeds_mut(store) += count;
+                events.push(MoveEvent::Sweep {
+                    from: hole,
+                    to: store,
+                    count,
+                });
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game of Oware from `position`, written in the crate's notation.
+    fn game(position: &str, grand_slam: GrandSlam) -> Oware {
+        let mut game = Oware::with_grand_slam(grand_slam);
+        assert!(game.set_position(position.parse().unwrap()));
+        game
+    }
+
+    fn hole(player: usize, hole: usize) -> Index {
+        Index::Player(Player(player), Hole(hole))
+    }
+
+    fn captures(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Capture { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn sweeps(events: &[MoveEvent]) -> Vec<(Index, usize)> {
+        events
+            .iter()
+            .filter_map(|event| match *event {
+                MoveEvent::Sweep { from, count, .. } => Some((from, count)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sowing_twelve_or_more_skips_the_origin() {
+        let mut game = game("12,0,0,0,0,0[0]/1,1,1,1,1,1[0] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 0));
+
+        assert!(!events.contains(&MoveEvent::Sow { to: hole(0, 0) }));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|event| matches!(event, MoveEvent::Sow { .. }))
+                .count(),
+            12
+        );
+        assert_eq!(
+            game.position().to_string(),
+            "0,2,1,1,1,1[0]/2,2,2,2,2,2[0] 1"
+        );
+    }
+
+    #[test]
+    fn captures_run_backwards_over_twos_and_threes() {
+        let mut game = game("0,0,0,0,0,3[0]/1,2,1,5,1,1[0] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 5));
+
+        assert_eq!(
+            captures(&events),
+            [(hole(1, 2), 2), (hole(1, 1), 3), (hole(1, 0), 2)]
+        );
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,0[7]/0,0,0,5,1,1[0] 1"
+        );
+    }
+
+    #[test]
+    fn captures_stop_at_the_first_other_count() {
+        let mut game = game("0,0,0,0,0,3[0]/1,0,1,5,1,1[0] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 5));
+
+        assert_eq!(captures(&events), [(hole(1, 2), 2)]);
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,0[2]/2,1,0,5,1,1[0] 1"
+        );
+    }
+
+    #[test]
+    fn own_row_is_never_captured() {
+        let mut game = game("0,0,0,1,1,0[0]/1,1,1,1,1,1[0] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 3));
+
+        assert!(captures(&events).is_empty());
+        assert_eq!(game.seeds(hole(0, 4)), 2);
+    }
+
+    /// Sowing `F` takes both of the opponent's seeds up to two each, a grand slam.
+    const GRAND_SLAM: &str = "1,0,0,0,0,2[0]/1,1,0,0,0,0[0] 0";
+
+    #[test]
+    fn grand_slam_captures_nothing_under_abapa() {
+        let mut game = game(GRAND_SLAM, GrandSlam::NoCapture);
+        assert_eq!(game.legal_moves(Player(0)), [hole(0, 0), hole(0, 5)]);
+        let events = game.perform_move(hole(0, 5));
+
+        assert!(captures(&events).is_empty());
+        assert_eq!(
+            game.position().to_string(),
+            "1,0,0,0,0,0[0]/2,2,0,0,0,0[0] 1"
+        );
+        assert_eq!(game.outcome(), None);
+    }
+
+    #[test]
+    fn grand_slam_captures_everything_when_allowed() {
+        let mut game = game(GRAND_SLAM, GrandSlam::Capture);
+        let events = game.perform_move(hole(0, 5));
+
+        // Four of the five seeds is a majority, so the seed left on `A` is never swept.
+        assert_eq!(captures(&events), [(hole(1, 1), 2), (hole(1, 0), 2)]);
+        assert_eq!(
+            game.position().to_string(),
+            "1,0,0,0,0,0[4]/0,0,0,0,0,0[0] 1"
+        );
+        assert_eq!(game.outcome(), Some(Outcome::Winner(Player(0))));
+    }
+
+    #[test]
+    fn grand_slam_cannot_be_played_when_forbidden() {
+        let game = game(GRAND_SLAM, GrandSlam::Forbidden);
+        assert_eq!(game.legal_moves(Player(0)), [hole(0, 0)]);
+    }
+
+    #[test]
+    fn an_empty_row_must_be_fed() {
+        let game = game("0,0,0,0,1,6[0]/0,0,0,0,0,0[5] 0", GrandSlam::default());
+        // `E` only reaches `F`, leaving the opponent nothing to play.
+        assert_eq!(game.legal_moves(Player(0)), [hole(0, 5)]);
+    }
+
+    #[test]
+    fn game_ends_when_nobody_can_feed() {
+        let mut game = game("0,0,0,0,0,1[0]/3,0,0,0,0,0[0] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 5));
+
+        // Four seeds from `a` can't reach the other side, so they are swept into their owner's
+        // store.
+        assert!(captures(&events).is_empty());
+        assert_eq!(sweeps(&events), [(hole(1, 0), 4)]);
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,0,0[0]/0,0,0,0,0,0[4] 1"
+        );
+        assert_eq!(game.outcome(), Some(Outcome::Winner(Player(1))));
+    }
+
+    #[test]
+    fn majority_counts_the_seeds_in_play() {
+        // Sixteen seeds in play, so ten in a store is a majority even with seeds left to sow.
+        let mut game = game("0,0,0,0,1,2[6]/1,1,1,1,1,1[1] 0", GrandSlam::default());
+        let events = game.perform_move(hole(0, 5));
+
+        assert_eq!(captures(&events), [(hole(1, 1), 2), (hole(1, 0), 2)]);
+        assert_eq!(
+            game.position().to_string(),
+            "0,0,0,0,1,0[10]/0,0,1,1,1,1[1] 1"
+        );
+        assert_eq!(game.outcome(), Some(Outcome::Winner(Player(0))));
+    }
+
+    #[test]
+    fn game_ends_when_a_position_repeats() {
+        let start = "0,0,0,0,0,1[0]/0,0,0,0,0,1[0] 0";
+        let mut game = game(start, GrandSlam::default());
+        // Each side walks its one seed round the board, never landing on a two or a three.
+        for _ in 0..11 {
+            let index = game.legal_moves(game.current_player())[0];
+            game.perform_move(index);
+            assert_eq!(game.outcome(), None);
+        }
+        let events = game.perform_move(hole(1, 4));
+
+        assert_eq!(sweeps(&events), [(hole(0, 5), 1), (hole(1, 5), 1)]);
+        assert_eq!(game.outcome(), Some(Outcome::Draw));
+        assert!(game.undo_move());
+        assert_eq!(game.outcome(), None);
+    }
+}
@@ -1,11 +1,46 @@
-use bevy::{app, prelude::*};
+use std::{fs, path::Path};
+
+use avian3d::prelude::{Collider, ColliderConstructor, ColliderConstructorHierarchy};
+use bevy::{
+    app::{self, AppExit},
+    asset::{io::Reader, AssetLoader, LoadContext, ReadAssetBytesError},
+    prelude::*,
+};
 use bevy_asset_loader::asset_collection::AssetCollection;
+use serde::{Deserialize, Serialize};
+use stones_rules::variants::custom::{Definition, DefinitionError};
+
+use crate::game::GameState;
+
+/// Pass this on the command line to write the colliders out to [`BAKED_DIR`] and quit.
+const BAKE_FLAG: &str = "--bake-colliders";
+
+/// Where baked colliders are written, relative to the crate.
+const BAKED_DIR: &str = "assets/colliders";
+
+/// The file in [`BAKED_DIR`] saying which models the colliders there were baked from.
+const MANIFEST: &str = "baked.colliders.ron";
+
+/// A fingerprint of the board and stone models, worked out by the build script.
+const MODELS_HASH: &str = env!("MODELS_HASH");
 
 pub(crate) struct Plugin;
 
 impl app::Plugin for Plugin {
-    fn build(&self, _app: &mut App) {
-        // app;
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BakedColliders>()
+            .init_asset_loader::<BakedCollidersLoader>()
+            .init_asset::<VariantDefinition>()
+            .init_asset_loader::<VariantDefinitionLoader>();
+
+        if std::env::args().any(|arg| arg == BAKE_FLAG) {
+            app.insert_resource(BakeColliders)
+                .add_systems(OnEnter(GameState::Menu), start_baking)
+                .add_systems(
+                    Update,
+                    finish_baking.run_if(resource_exists::<BakeColliders>),
+                );
+        }
     }
 }
 
@@ -22,13 +57,267 @@ pub struct GameAssets {
 
     #[asset(key = "main_font")]
     pub main_font: Handle<Font>,
+
+    /// A stone dropping into a hole, with a few takes so a long sowing doesn't sound mechanical.
+    #[asset(key = "drop_sounds", collection(typed))]
+    pub drop_sounds: Vec<Handle<AudioSource>>,
+
+    #[asset(key = "store_sound")]
+    pub store_sound: Handle<AudioSource>,
+
+    #[asset(key = "capture_sound")]
+    pub capture_sound: Handle<AudioSource>,
+
+    #[asset(key = "win_jingle")]
+    pub win_jingle: Handle<AudioSource>,
+
+    #[asset(key = "draw_jingle")]
+    pub draw_jingle: Handle<AudioSource>,
+
+    /// The variants described in variant files, on top of the ones built into the game.
+    #[asset(key = "variants", collection(typed))]
+    pub variants: Vec<Handle<VariantDefinition>>,
+
+    #[asset(key = "baked_colliders")]
+    pub baked_colliders: Handle<BakedColliders>,
+}
+
+/// The rules of a variant, read from a `.variant.ron` file.
+#[derive(Asset, TypePath, Debug, Deref)]
+pub struct VariantDefinition(Definition);
+
+#[derive(Default)]
+struct VariantDefinitionLoader;
+
+impl AssetLoader for VariantDefinitionLoader {
+    type Asset = VariantDefinition;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let definition = ron::de::from_bytes::<Definition>(&bytes)?;
+        definition.validate()?;
+        Ok(VariantDefinition(definition))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["variant.ron"]
+    }
+}
+
+/// The board and stone colliders, built ahead of time so they don't have to be worked out from the
+/// meshes on slow devices.
+///
+/// Either is `None` if they were never baked, or were baked from different models, in which case
+/// they are worked out from the meshes after all.
+#[derive(Asset, TypePath, Debug, Default)]
+pub struct BakedColliders {
+    board: Option<Collider>,
+    stone: Option<Collider>,
+}
+
+impl BakedColliders {
+    /// The whole board as one collider, or `None` if it wasn't baked.
+    pub fn board(&self) -> Option<Collider> {
+        self.board.clone()
+    }
+
+    /// A single stone's collider, or `None` if it wasn't baked.
+    pub fn stone(&self) -> Option<Collider> {
+        self.stone.clone()
+    }
+}
+
+/// What was baked, as written to [`MANIFEST`] beside the colliders.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    /// The [`MODELS_HASH`] of the models the colliders were baked from, or `None` if they never
+    /// were.
+    models: Option<String>,
+}
+
+#[derive(Default)]
+struct BakedCollidersLoader;
+
+impl AssetLoader for BakedCollidersLoader {
+    type Asset = BakedColliders;
+    type Settings = ();
+    type Error = Error;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let manifest = ron::de::from_bytes::<Manifest>(&bytes)?;
+        match manifest.models {
+            Some(models) if models == MODELS_HASH => {}
+            Some(_) => {
+                tracing::warn!("The colliders were baked from older models, so they'll be rebuilt");
+                return Ok(BakedColliders::default());
+            }
+            None => {
+                tracing::debug!("The colliders were never baked, so they'll be built");
+                return Ok(BakedColliders::default());
+            }
+        }
+
+        // A collider that can't be read is built from the meshes instead, like one never baked.
+        let usable = |name, collider: Result<Collider, Error>| {
+            collider
+                .inspect_err(|e| tracing::warn!("Unable to use the baked {name} collider: {e}"))
+                .ok()
+        };
+        Ok(BakedColliders {
+            board: usable("board", read_baked(load_context, "board").await),
+            stone: usable("stone", read_baked(load_context, "stone").await),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["colliders.ron"]
+    }
+}
+
+/// Reads the collider baked to `<name>.collider.msgpack` beside the manifest being loaded.
+async fn read_baked(load_context: &mut LoadContext<'_>, name: &str) -> Result<Collider, Error> {
+    let path = load_context
+        .path()
+        .with_file_name(format!("{name}.collider.msgpack"));
+    let bytes = load_context.read_asset_bytes(path).await?;
+    Ok(rmp_serde::from_slice(&bytes)?)
+}
+
+/// The colliders are being baked, instead of playing the game.
+#[derive(Debug, Clone, Copy, Resource)]
+struct BakeColliders;
+
+/// The board spawned to build its colliders from, so they can be baked.
+#[derive(Debug, Clone, Copy, Component)]
+struct BakingBoard;
+
+/// Bakes the stone collider, and spawns the board so the physics builds colliders for each of its
+/// meshes.
+fn start_baking(
+    game_assets: Res<GameAssets>,
+    meshes: Res<Assets<Mesh>>,
+    mut commands: Commands,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some(mesh) = meshes.get(&game_assets.stone_mesh) else {
+        tracing::error!("The stone mesh isn't loaded, so there is nothing to bake");
+        exit.send(AppExit::error());
+        return;
+    };
+    let Some(stone) = Collider::convex_hull_from_mesh(mesh) else {
+        tracing::error!("Unable to build a collider from the stone mesh");
+        exit.send(AppExit::error());
+        return;
+    };
+    if let Err(e) = write_baked("stone", &stone) {
+        tracing::error!("{e}");
+        exit.send(AppExit::error());
+        return;
+    }
+
+    commands.spawn((
+        BakingBoard,
+        Name::from("Baking Board"),
+        ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh),
+        SceneRoot::from(game_assets.board_scene.clone()),
+        Transform::default(),
+    ));
+}
+
+/// Once the board's colliders are built, combines them into one and bakes it.
+fn finish_baking(
+    board: Single<(Entity, Has<ColliderConstructorHierarchy>), With<BakingBoard>>,
+    children: Query<&Children>,
+    parts: Query<(&Collider, &GlobalTransform)>,
+    mut commands: Commands,
+    mut exit: EventWriter<AppExit>,
+) {
+    let (board, building) = *board;
+    if building {
+        return;
+    }
+
+    let parts = children
+        .iter_descendants(board)
+        .filter_map(|part| parts.get(part).ok())
+        .map(|(collider, transform)| {
+            let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+            let mut collider = collider.clone();
+            collider.set_scale(scale, 10);
+            (translation, rotation, collider)
+        })
+        .collect::<Vec<_>>();
+    commands.remove_resource::<BakeColliders>();
+    if parts.is_empty() {
+        tracing::error!("The board has no meshes to build colliders from");
+        exit.send(AppExit::error());
+        return;
+    }
+
+    if let Err(e) = write_baked("board", &Collider::compound(parts)).and_then(|()| write_manifest())
+    {
+        tracing::error!("{e}");
+        exit.send(AppExit::error());
+        return;
+    }
+    exit.send(AppExit::Success);
+}
+
+/// Writes `collider` to `<name>.collider.msgpack` in the baked colliders directory.
+fn write_baked(name: &str, collider: &Collider) -> Result<(), Error> {
+    fs::create_dir_all(BAKED_DIR)?;
+    let path = Path::new(BAKED_DIR).join(format!("{name}.collider.msgpack"));
+    fs::write(&path, rmp_serde::to_vec(collider)?)?;
+    tracing::info!("Baked {}", path.display());
+    Ok(())
+}
+
+/// Records which models the colliders were just baked from, so they're only used with those.
+fn write_manifest() -> Result<(), Error> {
+    let manifest = Manifest {
+        models: Some(MODELS_HASH.to_string()),
+    };
+    let path = Path::new(BAKED_DIR).join(MANIFEST);
+    fs::write(&path, ron::ser::to_string_pretty(&manifest, default())?)?;
+    tracing::info!("Baked {}", path.display());
+    Ok(())
 }
 
 #[derive(Debug, thiserror::Error)]
 pub(crate) enum Error {
-    #[error("Failed to read collider: {0}")]
+    #[error("Failed to read asset: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("Failed to read asset: {0}")]
+    ReadAssetBytes(#[from] ReadAssetBytesError),
+
     #[error("Failed to unpack collider: {0:?}")]
     MessagePackError(#[from] rmp_serde::decode::Error),
+
+    #[error("Failed to pack collider: {0:?}")]
+    MessagePackEncodeError(#[from] rmp_serde::encode::Error),
+
+    #[error("Failed to parse variant: {0}")]
+    RonError(#[from] ron::error::SpannedError),
+
+    #[error("Failed to write manifest: {0}")]
+    RonWriteError(#[from] ron::Error),
+
+    #[error("Variant can't be played: {0}")]
+    InvalidVariant(#[from] DefinitionError),
 }
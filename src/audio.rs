@@ -0,0 +1,194 @@
+//! Sound effects for the stones and the end of the game, played in response to the game's
+//! [events](crate::events) so nothing else has to know about them.
+
+use bevy::{
+    app,
+    audio::{PlaybackMode, Volume},
+    prelude::*,
+};
+use bevy_prefs_lite::Preferences;
+use stones_rules::variants::{Index, Outcome};
+
+use crate::{
+    assets::GameAssets,
+    events::{GameOver, SeedSown, SeedsCaptured, SeedsSwept},
+    ui::settings::{load_settings, store_settings},
+};
+
+const GROUP: &str = "audio";
+
+/// How many seeds a hole holds before another one doesn't sound any different.
+const FULL_HOLE: usize = 12;
+
+pub(crate) struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SoundSettings>()
+            .add_systems(Startup, load_sound_settings)
+            .add_systems(
+                Update,
+                (
+                    apply_sound_settings.run_if(resource_changed::<SoundSettings>),
+                    (play_drops, play_captures, play_sweeps, play_jingle)
+                        .run_if(resource_exists::<GameAssets>),
+                ),
+            );
+    }
+}
+
+/// How loud the game is, in percent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct SoundSettings {
+    /// Scales every sound the game makes.
+    pub master: u32,
+    /// Scales the stones and jingles, on top of `master`.
+    pub effects: u32,
+}
+
+impl Default for SoundSettings {
+    fn default() -> Self {
+        Self {
+            master: 80,
+            effects: 100,
+        }
+    }
+}
+
+impl SoundSettings {
+    /// How far each click on a volume setting turns it up.
+    const STEP: u32 = 10;
+
+    pub fn next_master(&mut self) {
+        self.master = Self::next_volume(self.master);
+    }
+
+    pub fn next_effects(&mut self) {
+        self.effects = Self::next_volume(self.effects);
+    }
+
+    /// Turns `volume` up a step, going back to silent once it is at full volume.
+    fn next_volume(volume: u32) -> u32 {
+        if volume >= 100 {
+            0
+        } else {
+            (volume + Self::STEP).min(100)
+        }
+    }
+
+    /// Plays `sound` once, at `volume` before the settings are applied and `speed` times as fast.
+    fn play(&self, commands: &mut Commands, sound: Handle<AudioSource>, volume: f32, speed: f32) {
+        let volume = volume * self.effects as f32 / 100.0;
+        commands.spawn((
+            AudioPlayer::new(sound),
+            PlaybackSettings {
+                mode: PlaybackMode::Despawn,
+                volume: Volume::new(volume),
+                speed,
+                ..Default::default()
+            },
+        ));
+    }
+}
+
+/// Picks up the volumes from last time.
+fn load_sound_settings(prefs: Res<Preferences>, mut settings: ResMut<SoundSettings>) {
+    load_settings(&prefs, GROUP, |group| {
+        if let Some(master) = group.get::<u32>("master") {
+            settings.master = master.min(100);
+        }
+        if let Some(effects) = group.get::<u32>("effects") {
+            settings.effects = effects.min(100);
+        }
+    });
+}
+
+/// Turns the master volume to match the settings, and remembers any change for next time.
+fn apply_sound_settings(
+    settings: Res<SoundSettings>,
+    mut global: ResMut<GlobalVolume>,
+    mut prefs: ResMut<Preferences>,
+    mut commands: Commands,
+) {
+    *global = GlobalVolume::new(settings.master as f32 / 100.0);
+    // The settings were only just loaded, so there is nothing new to remember.
+    if settings.is_added() {
+        return;
+    }
+
+    store_settings(&mut prefs, &mut commands, GROUP, "the volume", |group| {
+        group.set("master", settings.master);
+        group.set("effects", settings.effects);
+    });
+}
+
+/// Clicks each stone into place as it lands.
+///
+/// Stones landing on a pile of others sound higher and quieter than ones hitting the bare wood.
+fn play_drops(
+    mut sown: EventReader<SeedSown>,
+    game_assets: Res<GameAssets>,
+    settings: Res<SoundSettings>,
+    mut commands: Commands,
+) {
+    for &SeedSown { to, filled } in sown.read() {
+        let fill = filled.min(FULL_HOLE) as f32 / FULL_HOLE as f32;
+        match to {
+            Index::Score(_) => {
+                let sound = game_assets.store_sound.clone();
+                settings.play(&mut commands, sound, 0.8, 1.0 + 0.1 * fill);
+            }
+            Index::Player(..) => {
+                // Taking turns between the takes, so no two stones in a row sound the same.
+                let Some(sound) = game_assets.drop_sounds.iter().cycle().nth(filled) else {
+                    continue;
+                };
+                let (volume, speed) = (1.0 - 0.4 * fill, 1.0 + 0.25 * fill);
+                settings.play(&mut commands, sound.clone(), volume, speed);
+            }
+        }
+    }
+}
+
+/// Rattles the captured stones into the store, louder the more there are.
+fn play_captures(
+    mut captured: EventReader<SeedsCaptured>,
+    game_assets: Res<GameAssets>,
+    settings: Res<SoundSettings>,
+    mut commands: Commands,
+) {
+    for &SeedsCaptured { count, .. } in captured.read() {
+        let haul = count.min(FULL_HOLE) as f32 / FULL_HOLE as f32;
+        let sound = game_assets.capture_sound.clone();
+        settings.play(&mut commands, sound, 0.7 + 0.3 * haul, 1.0);
+    }
+}
+
+/// Rattles the seeds left at the end of the game into the stores, more quietly than a capture.
+fn play_sweeps(
+    mut swept: EventReader<SeedsSwept>,
+    game_assets: Res<GameAssets>,
+    settings: Res<SoundSettings>,
+    mut commands: Commands,
+) {
+    for &SeedsSwept { count, .. } in swept.read() {
+        let haul = count.min(FULL_HOLE) as f32 / FULL_HOLE as f32;
+        let sound = game_assets.capture_sound.clone();
+        settings.play(&mut commands, sound, 0.3 + 0.2 * haul, 0.8);
+    }
+}
+
+fn play_jingle(
+    mut game_over: EventReader<GameOver>,
+    game_assets: Res<GameAssets>,
+    settings: Res<SoundSettings>,
+    mut commands: Commands,
+) {
+    for &GameOver { outcome } in game_over.read() {
+        let sound = match outcome {
+            Outcome::Winner(_) => game_assets.win_jingle.clone(),
+            Outcome::Draw => game_assets.draw_jingle.clone(),
+        };
+        settings.play(&mut commands, sound, 1.0, 1.0);
+    }
+}
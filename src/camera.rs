@@ -0,0 +1,360 @@
+//! Moving the camera around the board: orbiting and zooming by hand, jumping between preset views,
+//! and optionally turning to face whoever's turn it is.
+
+use std::f32::consts::{FRAC_PI_2, FRAC_PI_4, PI, SQRT_2, TAU};
+
+use bevy::{
+    app,
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll, MouseScrollUnit},
+    prelude::*,
+};
+use bevy_prefs_lite::Preferences;
+
+use crate::{
+    events::TurnChanged,
+    game::{ai::Players, Board, InGame, Player},
+    ui::settings::{load_settings, store_settings},
+};
+
+const GROUP: &str = "camera";
+
+/// Where the camera starts each game, which is where the human sits when playing the computer.
+const DEFAULT_SEAT: Player = Player(1);
+
+/// How far the camera is from the middle of a standard board.
+const DEFAULT_DISTANCE: f32 = 0.45 * SQRT_2;
+
+/// How close and how far the camera may be zoomed, as multiples of the default distance.
+const ZOOM_LIMITS: (f32, f32) = (0.4, 2.5);
+
+/// The lowest the camera may look across the board from, in radians above level.
+const MIN_PITCH: f32 = 0.15;
+
+/// How far the camera turns for each pixel the mouse or a finger is dragged, in radians.
+const DRAG_SPEED: f32 = 0.005;
+
+/// How much closer each line the mouse wheel is scrolled brings the camera.
+const SCROLL_ZOOM: f32 = 0.9;
+
+/// How many pixels of scrolling on a touchpad count as a line of the mouse wheel.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// How fast a stick pushed all the way turns the camera, in radians a second.
+const STICK_SPEED: f32 = 2.0;
+
+/// How fast holding a bumper zooms, as the power of `e` the distance changes by each second.
+const BUMPER_ZOOM: f32 = 1.0;
+
+/// How quickly the camera catches up with where it's headed. Higher is snappier.
+const EASING: f32 = 8.0;
+
+pub(crate) struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraSettings>()
+            .add_systems(Startup, load_camera_settings)
+            .add_systems(OnEnter(InGame), reset_view)
+            .add_systems(
+                Update,
+                (
+                    store_camera_settings.run_if(resource_changed::<CameraSettings>),
+                    (
+                        (
+                            orbit_with_mouse,
+                            orbit_with_touch,
+                            orbit_with_gamepad,
+                            cycle_views,
+                            follow_current_player,
+                        ),
+                        ease_camera,
+                    )
+                        .chain()
+                        .run_if(in_state(InGame)),
+                ),
+            );
+    }
+}
+
+/// How the camera behaves, beyond where the player points it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct CameraSettings {
+    /// Turn to face each human player when their turn comes round, for passing one device around.
+    pub follow_player: bool,
+}
+
+impl CameraSettings {
+    pub fn toggle_follow_player(&mut self) {
+        self.follow_player = !self.follow_player;
+    }
+}
+
+/// A point on a sphere around the middle of the board, for the camera to look from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Orbit {
+    /// How far round the board, in radians, with `0` looking from the near edge.
+    yaw: f32,
+    /// How far above the board, in radians, from level to straight down.
+    pitch: f32,
+    distance: f32,
+}
+
+impl Default for Orbit {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: FRAC_PI_4,
+            distance: DEFAULT_DISTANCE,
+        }
+    }
+}
+
+impl Orbit {
+    /// The camera at this point, looking at the middle of the board.
+    fn transform(&self) -> Transform {
+        let rotation = Quat::from_euler(EulerRot::YXZ, self.yaw, -self.pitch, 0.0);
+        Transform::from_translation(rotation * Vec3::Z * self.distance).with_rotation(rotation)
+    }
+
+    /// Moves `t` of the way to `target`, going the short way round.
+    fn ease_towards(&mut self, target: &Orbit, t: f32) {
+        let turn = (target.yaw - self.yaw + PI).rem_euclid(TAU) - PI;
+        self.yaw += turn * t;
+        self.pitch += (target.pitch - self.pitch) * t;
+        self.distance += (target.distance - self.distance) * t;
+    }
+}
+
+/// The views the camera can jump to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// Straight down onto the board.
+    Top,
+    /// From where `Player` sits, looking across their side of the board.
+    Seat(Player),
+}
+
+impl View {
+    /// The view after this one: from above, and then from each player's seat in turn.
+    fn next(self, player_count: usize) -> Self {
+        match self {
+            View::Top => View::Seat(Player(0)),
+            View::Seat(Player(p)) if p + 1 < player_count => View::Seat(Player(p + 1)),
+            View::Seat(_) => View::Top,
+        }
+    }
+
+    /// Where the camera looks from for this view of `board`, keeping its heading from `current`
+    /// when looking straight down.
+    fn orbit(self, board: &Board, current: &Orbit) -> Orbit {
+        let distance = DEFAULT_DISTANCE * board_size(board);
+        match self {
+            View::Top => Orbit {
+                yaw: current.yaw,
+                pitch: FRAC_PI_2,
+                distance,
+            },
+            View::Seat(player) => {
+                let seat = board.seat_direction(player);
+                Orbit {
+                    yaw: seat.x.atan2(seat.z),
+                    pitch: FRAC_PI_4,
+                    distance,
+                }
+            }
+        }
+    }
+}
+
+/// How much bigger than the standard board `board` is, so the camera can be pulled back to fit it.
+fn board_size(board: &Board) -> f32 {
+    board.scale().max_element().max(1.0)
+}
+
+/// Moves the camera around the board, easing it from where it is towards where it's headed.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct OrbitCamera {
+    current: Orbit,
+    target: Orbit,
+    /// The preset view last jumped to, which the next one follows on from.
+    view: View,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            current: Orbit::default(),
+            target: Orbit::default(),
+            view: View::Seat(DEFAULT_SEAT),
+        }
+    }
+}
+
+impl OrbitCamera {
+    /// Where the camera is right now.
+    pub fn transform(&self) -> Transform {
+        self.current.transform()
+    }
+
+    /// Heads for `view` of `board`.
+    pub fn show(&mut self, view: View, board: &Board) {
+        self.view = view;
+        self.target = view.orbit(board, &self.target);
+    }
+
+    /// Heads for the preset view after the last one.
+    pub fn cycle_view(&mut self, board: &Board) {
+        self.show(self.view.next(board.player_count()), board);
+    }
+
+    /// Turns the camera by `yaw` and `pitch` radians, and zooms it in by `zoom` times.
+    fn nudge(&mut self, yaw: f32, pitch: f32, zoom: f32, board: &Board) {
+        let size = DEFAULT_DISTANCE * board_size(board);
+        let target = &mut self.target;
+        target.yaw += yaw;
+        target.pitch = (target.pitch + pitch).clamp(MIN_PITCH, FRAC_PI_2);
+        target.distance =
+            (target.distance / zoom).clamp(size * ZOOM_LIMITS.0, size * ZOOM_LIMITS.1);
+    }
+}
+
+/// Puts the camera in its starting seat for the board that was just set up.
+fn reset_view(mut camera: Single<&mut OrbitCamera>, board: Res<Board>) {
+    camera.show(View::Seat(DEFAULT_SEAT), &board);
+    camera.current = camera.target;
+}
+
+/// Orbits while the right button is held and the mouse dragged, and zooms with the wheel.
+fn orbit_with_mouse(
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut camera: Single<&mut OrbitCamera>,
+    board: Res<Board>,
+) {
+    let drag = if buttons.pressed(MouseButton::Right) {
+        motion.delta * DRAG_SPEED
+    } else {
+        Vec2::ZERO
+    };
+    let lines = match scroll.unit {
+        MouseScrollUnit::Line => scroll.delta.y,
+        MouseScrollUnit::Pixel => scroll.delta.y / PIXELS_PER_LINE,
+    };
+    if drag != Vec2::ZERO || lines != 0.0 {
+        camera.nudge(-drag.x, drag.y, SCROLL_ZOOM.powf(-lines), &board);
+    }
+}
+
+/// Orbits when two fingers are dragged together, and zooms when they are pinched.
+///
+/// A single finger is left alone, for picking holes.
+fn orbit_with_touch(
+    touches: Res<Touches>,
+    mut camera: Single<&mut OrbitCamera>,
+    board: Res<Board>,
+) {
+    let mut fingers = touches.iter();
+    let (Some(a), Some(b), None) = (fingers.next(), fingers.next(), fingers.next()) else {
+        return;
+    };
+
+    let drag = (a.delta() + b.delta()) / 2.0 * DRAG_SPEED;
+    let before = a.previous_position().distance(b.previous_position());
+    let after = a.position().distance(b.position());
+    let zoom = if before > 0.0 { after / before } else { 1.0 };
+    camera.nudge(-drag.x, drag.y, zoom, &board);
+}
+
+/// Orbits with the right stick, and zooms in and out with the right and left bumpers.
+///
+/// Bevy calls the bumpers `RightTrigger` and `LeftTrigger`, and the triggers behind them
+/// `RightTrigger2` and `LeftTrigger2`.
+fn orbit_with_gamepad(
+    gamepads: Query<&Gamepad>,
+    time: Res<Time>,
+    mut camera: Single<&mut OrbitCamera>,
+    board: Res<Board>,
+) {
+    let seconds = time.delta_secs();
+    for gamepad in &gamepads {
+        let stick = gamepad.right_stick() * STICK_SPEED * seconds;
+        let zoom = match (
+            gamepad.pressed(GamepadButton::RightTrigger),
+            gamepad.pressed(GamepadButton::LeftTrigger),
+        ) {
+            (true, false) => (BUMPER_ZOOM * seconds).exp(),
+            (false, true) => (-BUMPER_ZOOM * seconds).exp(),
+            _ => 1.0,
+        };
+        if stick != Vec2::ZERO || zoom != 1.0 {
+            camera.nudge(-stick.x, stick.y, zoom, &board);
+        }
+    }
+}
+
+/// Jumps to the next preset view when `C` or a gamepad's select button is pressed.
+fn cycle_views(
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut camera: Single<&mut OrbitCamera>,
+    board: Res<Board>,
+) {
+    let pressed = keys.just_pressed(KeyCode::KeyC)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Select));
+    if pressed {
+        camera.cycle_view(&board);
+    }
+}
+
+/// Turns to face each human player as their turn comes round, if the settings ask for it.
+fn follow_current_player(
+    mut turns: EventReader<TurnChanged>,
+    settings: Res<CameraSettings>,
+    players: Res<Players>,
+    mut camera: Single<&mut OrbitCamera>,
+    board: Res<Board>,
+) {
+    for &TurnChanged { player } in turns.read() {
+        if settings.follow_player && players.is_human(player) {
+            camera.show(View::Seat(player), &board);
+        }
+    }
+}
+
+fn ease_camera(time: Res<Time>, camera: Single<(&mut OrbitCamera, &mut Transform)>) {
+    let (mut camera, mut transform) = camera.into_inner();
+    let target = camera.target;
+    camera
+        .current
+        .ease_towards(&target, 1.0 - (-EASING * time.delta_secs()).exp());
+    *transform = camera.transform();
+}
+
+/// Picks up the camera settings from last time.
+fn load_camera_settings(prefs: Res<Preferences>, mut settings: ResMut<CameraSettings>) {
+    load_settings(&prefs, GROUP, |group| {
+        if let Some(follow_player) = group.get::<bool>("follow_player") {
+            settings.follow_player = follow_player;
+        }
+    });
+}
+
+/// Remembers any change to the camera settings for next time.
+fn store_camera_settings(
+    settings: Res<CameraSettings>,
+    mut prefs: ResMut<Preferences>,
+    mut commands: Commands,
+) {
+    // The settings were only just loaded, so there is nothing new to remember.
+    if settings.is_added() {
+        return;
+    }
+
+    let what = "the camera settings";
+    store_settings(&mut prefs, &mut commands, GROUP, what, |group| {
+        group.set("follow_player", settings.follow_player);
+    });
+}
@@ -1,8 +1,89 @@
+//! What happens over the course of a game, sent as events for sound, statistics and the like to
+//! react to, rather than being wired into the rules or the animations.
+//!
+//! The seed events are sent as the stones are animated, so they line up with what is on screen,
+//! and are sent while a record is replayed too. The rest are only sent for games being played.
+
 use bevy::{app, prelude::*};
+use stones_rules::variants::{Index, Outcome};
 
+use crate::game::Player;
 
 pub struct Plugin;
 
 impl app::Plugin for Plugin {
-    fn build(&self, _app: &mut App) {}
+    fn build(&self, app: &mut App) {
+        app.add_event::<MoveStarted>()
+            .add_event::<SeedsPickedUp>()
+            .add_event::<SeedSown>()
+            .add_event::<SeedsCaptured>()
+            .add_event::<SeedsSwept>()
+            .add_event::<ExtraTurn>()
+            .add_event::<TurnChanged>()
+            .add_event::<MoveTakenBack>()
+            .add_event::<GameOver>();
+    }
+}
+
+/// `player` chose to sow from `from`, and the move is about to be animated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct MoveStarted {
+    pub player: Player,
+    pub from: Index,
+}
+
+/// `count` seeds were lifted out of `from` to be sown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct SeedsPickedUp {
+    pub from: Index,
+    pub count: usize,
+}
+
+/// A seed landed in the hole or store at `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct SeedSown {
+    pub to: Index,
+    /// How many seeds are in `to` now, counting this one.
+    pub filled: usize,
+}
+
+/// `count` seeds were captured from `from` and have all landed in the store `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct SeedsCaptured {
+    pub from: Index,
+    pub to: Index,
+    pub count: usize,
+}
+
+/// The game is over, and the `count` seeds left in `from` have all landed in the store `to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct SeedsSwept {
+    pub from: Index,
+    pub to: Index,
+    pub count: usize,
+}
+
+/// `player` moved, and gets to move again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct ExtraTurn {
+    pub player: Player,
+}
+
+/// It is now `player`'s turn, whether after a move, an extra turn or a move being taken back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct TurnChanged {
+    pub player: Player,
+}
+
+/// The move `player` made from `from` was taken back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct MoveTakenBack {
+    pub player: Player,
+    pub from: Index,
+}
+
+/// The game is over, and turned out as `outcome`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Event)]
+pub struct GameOver {
+    pub outcome: Outcome,
 }
@@ -1,50 +1,88 @@
-use std::hash::Hash;
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    hash::Hash,
+};
 
 use bevy::{
     app,
-    color::palettes::css::{DARK_CYAN, GOLD, GREEN, SLATE_GRAY},
+    color::palettes::css::{CRIMSON, DARK_CYAN, GOLD, GREEN, ORANGE, SLATE_GRAY},
+    ecs::system::SystemParam,
     prelude::*,
+    utils::HashMap,
 };
 use bevy_asset_loader::{
     loading_state::{config::ConfigureLoadingState, LoadingState, LoadingStateAppExt},
     standard_dynamic_asset::StandardDynamicAssetCollection,
 };
 use bevy_sequential_actions::{ActionsProxy, ModifyActions, SequentialActions};
+use stones_rules::{
+    variants::{ChosenVariant, Direction, Index, Variant},
+    HOLE_COUNT,
+};
 
 use crate::{
     assets::GameAssets,
-    rules::variants::{ChosenVariant, Index, Variant},
+    camera::OrbitCamera,
     ui::{hover_button, unhover_button},
+    MAX_PLAYER_COUNT,
+};
+
+use self::{
+    actions::{
+        board::SpawnBoardAndPieces,
+        turn::{NextPlayer, Settle},
+        undo::{spawn_undo_buttons, Undo},
+    },
+    ai::Players,
+    save::ResumeGame,
 };
 
-use self::actions::{board::SpawnBoardAndPieces, turn::NextPlayer};
+pub use stones_rules::{Hole, Player};
 
 pub mod actions;
+pub mod ai;
+pub mod focus;
+pub mod preview;
+pub mod record;
+pub mod replay;
+pub mod save;
 
 pub const BALL_RADIUS: f32 = 0.007;
 
+/// What each player is called on screen, after the colour their score is shown in.
+pub const PLAYER_NAMES: [&str; MAX_PLAYER_COUNT] = ["Blue", "Green", "Red", "Orange"];
+
 pub struct Plugin;
 
 impl app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(actions::Plugin)
-            .insert_resource(ChosenVariant::default())
-            .insert_resource(Board(ChosenVariant::default().to_variant()))
-            .insert_resource(PlayerTurn::None)
-            .insert_resource(Selected(None))
-            .register_type::<Player>()
-            .register_type::<Hole>()
-            .init_state::<GameState>()
-            .enable_state_scoped_entities::<GameState>()
-            .add_loading_state(
-                LoadingState::new(GameState::Loading)
-                    .continue_to_state(GameState::Menu)
-                    .with_dynamic_assets_file::<StandardDynamicAssetCollection>(
-                        "dynamic.assets.ron",
-                    )
-                    .load_collection::<GameAssets>(),
-            )
-            .add_systems(OnEnter(GameState::Playing), (setup_state, setup_ui));
+        app.add_plugins((
+            actions::Plugin,
+            ai::Plugin,
+            focus::Plugin,
+            preview::Plugin,
+            record::Plugin,
+            replay::Plugin,
+        ))
+        .insert_resource(ChosenVariant::default())
+        .insert_resource(Board::new(ChosenVariant::default().to_variant()))
+        .insert_resource(PlayerTurn::None)
+        .insert_resource(Selected(None))
+        .register_type::<Player>()
+        .register_type::<Hole>()
+        .init_state::<GameState>()
+        .add_computed_state::<InGame>()
+        .enable_state_scoped_entities::<GameState>()
+        .enable_state_scoped_entities::<InGame>()
+        .add_loading_state(
+            LoadingState::new(GameState::Loading)
+                .continue_to_state(GameState::Menu)
+                .with_dynamic_assets_file::<StandardDynamicAssetCollection>(
+                    "dynamic.assets.ron",
+                )
+                .load_collection::<GameAssets>(),
+        )
+        .add_systems(OnEnter(GameState::Playing), (setup_state, setup_ui).chain());
     }
 }
 
@@ -54,27 +92,29 @@ pub enum GameState {
     Loading,
 
     Menu,
+    /// Choosing the variant and players for the next game.
+    NewGame,
+    /// Changing the settings that apply to every game.
+    Settings,
+    /// Picking a recorded game to play through again.
+    Records,
     Playing,
+    /// Stepping through a recorded game, without making any moves.
+    Replay,
 }
 
-#[derive(Debug, Default, Clone, Copy, Component, Reflect, PartialEq, Eq, Hash, Deref, DerefMut)]
-pub struct Player(pub usize);
+/// Whether the board is on screen, either to play on or to watch a game being replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InGame;
 
-impl Player {
-    pub fn next(this: impl Into<Self>) -> Self {
-        Self((this.into().0 + 1) % 2)
-    }
-}
+impl ComputedStates for InGame {
+    type SourceStates = GameState;
 
-impl From<usize> for Player {
-    fn from(value: usize) -> Self {
-        Self(value)
+    fn compute(sources: GameState) -> Option<Self> {
+        matches!(sources, GameState::Playing | GameState::Replay).then_some(InGame)
     }
 }
 
-#[derive(Debug, Default, Clone, Copy, Component, Reflect, PartialEq, Eq, Hash, Deref, DerefMut)]
-pub struct Hole(pub usize);
-
 #[derive(Debug, Default, Clone, Copy, Component, Reflect, PartialEq, Eq, Hash)]
 pub struct Stone;
 
@@ -84,8 +124,264 @@ pub struct Score;
 #[derive(Debug, Default, Clone, Copy, Component, Reflect, PartialEq, Eq, Hash)]
 pub struct Turn;
 
-#[derive(Debug, Resource, Reflect, Deref, DerefMut)]
-pub struct Board(Box<dyn Variant + 'static>);
+/// The rules of the game being played, along with the stones sitting in each bucket.
+#[derive(Debug, Resource, Deref, DerefMut)]
+pub struct Board {
+    #[deref]
+    variant: Box<dyn Variant + 'static>,
+    stones: HashMap<Index, Vec<Entity>>,
+}
+
+impl Board {
+    /// How far apart neighbouring holes are along a row.
+    const HOLE_SPACING: f32 = 0.086;
+    /// How far the front row of holes is from the middle of a two-player board.
+    const ROW_OFFSET: f32 = 0.035;
+    /// How far apart the rows are on boards with more than one row a side.
+    const ROW_SPACING: f32 = 0.07;
+    /// How far the stores are beyond the last hole of each row.
+    const STORE_GAP: f32 = 0.061;
+    /// The height stones are dropped from.
+    const DROP_HEIGHT: f32 = 0.075;
+    /// How far from its middle a stone can sit and still be in a hole.
+    const HOLE_RADIUS: f32 = 0.04;
+    /// How far from its middle a stone can sit and still be in a store, which is longer than a
+    /// hole.
+    const STORE_RADIUS: f32 = 0.07;
+    /// How far beyond its hole a seed count is shown, on boards with one row a side.
+    const LABEL_OFFSET: f32 = 0.1;
+    /// How far above its hole a seed count is shown, on boards where the next row is in the way.
+    const LABEL_HEIGHT: f32 = 0.06;
+    /// How far the rim around a hole is from its middle, on boards built from pieces.
+    const CUP_RADIUS: f32 = 0.03;
+    /// How far the rim around a store is from its middle, along the board and across it.
+    const STORE_CUP: Vec2 = Vec2::new(0.022, 0.055);
+    /// How thick the rims around the holes and stores are.
+    const RIM_WIDTH: f32 = 0.004;
+    /// How high the rims around the holes and stores stand.
+    const RIM_HEIGHT: f32 = 0.02;
+    /// How far the slab under a board built from pieces reaches beyond the outermost rims.
+    const SLAB_MARGIN: f32 = 0.02;
+    /// How thick the slab under a board built from pieces is.
+    const SLAB_THICKNESS: f32 = 0.02;
+
+    pub fn new(variant: Box<dyn Variant>) -> Self {
+        Self {
+            variant,
+            stones: HashMap::default(),
+        }
+    }
+
+    /// Every hole and store on the board, player by player.
+    pub fn buckets(&self) -> impl Iterator<Item = Index> + '_ {
+        (0..self.player_count()).flat_map(move |p| {
+            (0..self.position().side_hole_count())
+                .map(move |h| Index::Player(Player(p), Hole(h)))
+                .chain([Index::Score(Player(p))])
+        })
+    }
+
+    /// Where stones should be dropped to land in the bucket at `index`.
+    ///
+    /// Player `0`'s side is laid out at the far edge of the board, with their rows sown left to
+    /// right and right to left in turn, ending with the row nearest the middle, and their store
+    /// to the right. Every other side is the same, turned around the middle of the board: half a
+    /// turn for the second of two players, or a quarter turn each for four. Variants sown the
+    /// other way round are mirrored end to end.
+    pub fn bucket_position(&self, index: Index) -> Vec3 {
+        let holes = self.hole_count();
+        let middle = (holes - 1) as f32 / 2.0;
+        let front = self.front_offset();
+        let (player, local) = match index {
+            Index::Player(player, Hole(h)) => {
+                assert!(*player < self.player_count(), "Invalid player index");
+                assert!(h < self.position().side_hole_count(), "Invalid hole index");
+                let (row, column) = (h / holes, h % holes);
+                let column = if row % 2 == 0 {
+                    column
+                } else {
+                    holes - 1 - column
+                };
+                let depth = front + (self.row_count() - 1 - row) as f32 * Self::ROW_SPACING;
+                let x = (column as f32 - middle) * Self::HOLE_SPACING;
+                (player, Vec3::new(x, 0.0, -depth))
+            }
+            Index::Score(player) => {
+                assert!(*player < self.player_count(), "Invalid player index");
+                let x = middle * Self::HOLE_SPACING + Self::STORE_GAP;
+                (player, Vec3::new(x, 0.0, Self::ROW_OFFSET - front))
+            }
+        };
+        let mut position = self.mirror(self.side_rotation(player) * local);
+        position.y = Self::DROP_HEIGHT;
+        position
+    }
+
+    /// Where the number of seeds in the hole at `index` is shown.
+    pub fn label_position(&self, index: Index) -> Vec3 {
+        let mut bucket = self.bucket_position(index);
+        if self.row_count() > 1 {
+            bucket.y = Self::LABEL_HEIGHT;
+            return bucket;
+        }
+        let Index::Player(player, _) = index else {
+            return bucket;
+        };
+        bucket.y = 0.01;
+        bucket + self.seat_direction(player) * Self::LABEL_OFFSET
+    }
+
+    /// Which way `player` sits from the middle of the board, looking across their side of it.
+    pub fn seat_direction(&self, player: Player) -> Vec3 {
+        self.mirror(self.side_rotation(player) * Vec3::NEG_Z)
+    }
+
+    /// How far the front row of each side is from the middle of the board, far enough that the
+    /// sides don't overlap when there are more than two.
+    fn front_offset(&self) -> f32 {
+        let players = self.player_count();
+        if players <= 2 {
+            return Self::ROW_OFFSET;
+        }
+        let half_length =
+            (self.hole_count() - 1) as f32 / 2.0 * Self::HOLE_SPACING + Self::STORE_GAP;
+        (half_length / (PI / players as f32).tan()).max(Self::ROW_OFFSET)
+    }
+
+    /// How far `player`'s side is turned around the middle of the board from player `0`'s.
+    fn side_rotation(&self, Player(player): Player) -> Quat {
+        Quat::from_rotation_y(-TAU * player as f32 / self.player_count() as f32)
+    }
+
+    /// Mirrors `position` end to end if the variant is sown the other way round.
+    fn mirror(&self, position: Vec3) -> Vec3 {
+        match self.direction() {
+            Direction::Counterclockwise => position,
+            Direction::Clockwise => Vec3::new(-position.x, position.y, position.z),
+        }
+    }
+
+    /// Whether the board model fits this layout, which it only does for the standard two players
+    /// with six holes a side. Any other board is built from pieces instead.
+    pub fn fits_model(&self) -> bool {
+        self.player_count() == 2 && self.row_count() == 1 && self.hole_count() == HOLE_COUNT
+    }
+
+    /// How far the board reaches from its middle, along it and across it, to take in every side.
+    fn reach(&self) -> Vec2 {
+        let side = self.side_reach();
+        let corners = [
+            Vec3::new(-side.x, 0.0, 0.0),
+            Vec3::new(side.x, 0.0, 0.0),
+            Vec3::new(-side.x, 0.0, -side.y),
+            Vec3::new(side.x, 0.0, -side.y),
+        ];
+        (0..self.player_count())
+            .flat_map(|player| {
+                let facing = self.side_facing(Player(player));
+                corners.map(|corner| (facing * corner).xz().abs())
+            })
+            .fold(Vec2::ZERO, Vec2::max)
+    }
+
+    /// How far each side reaches along itself from its middle, and back from the middle of the
+    /// board, to take in the rims of its holes and its store.
+    fn side_reach(&self) -> Vec2 {
+        let middle = (self.hole_count() - 1) as f32 / 2.0;
+        let store = self.rim_reach(Index::Score(Player(0)));
+        let length = middle * Self::HOLE_SPACING + Self::STORE_GAP + store.x;
+        let back = self.front_offset()
+            + (self.row_count() - 1) as f32 * Self::ROW_SPACING
+            + Self::CUP_RADIUS
+            + Self::RIM_WIDTH;
+        Vec2::new(length, back) + Vec2::splat(Self::SLAB_MARGIN)
+    }
+
+    /// Turns player `0`'s side to face the way `player`'s does, mirrored or not.
+    fn side_facing(&self, player: Player) -> Quat {
+        let seat = self.seat_direction(player);
+        Quat::from_rotation_y((-seat.x).atan2(-seat.z))
+    }
+
+    /// How far the inside of the rim around the bucket at `index` is from its middle, along the
+    /// board and across it.
+    fn cup(&self, index: Index) -> Vec2 {
+        match index {
+            Index::Player(_, _) => Vec2::splat(Self::CUP_RADIUS),
+            Index::Score(_) => Self::STORE_CUP,
+        }
+    }
+
+    /// How far the outside of the rim around the bucket at `index` is from its middle, along the
+    /// board and across it, since drawing a store out draws its rim out too.
+    fn rim_reach(&self, index: Index) -> Vec2 {
+        let cup = self.cup(index);
+        cup * (1.0 + Self::RIM_WIDTH / cup.x)
+    }
+
+    /// How much bigger than the standard board this one is, along it and across it.
+    pub fn scale(&self) -> Vec2 {
+        let store = Index::Score(Player(0));
+        let length = (HOLE_COUNT - 1) as f32 / 2.0 * Self::HOLE_SPACING + Self::STORE_GAP;
+        let across = Self::ROW_OFFSET + Self::CUP_RADIUS + Self::RIM_WIDTH;
+        let standard = Vec2::new(length + self.rim_reach(store).x, across);
+        self.reach() / (standard + Vec2::splat(Self::SLAB_MARGIN))
+    }
+
+    /// The slab under `player`'s side of a board built from pieces, and where it goes, with its
+    /// top level with the bottom of the holes.
+    ///
+    /// Each slab reaches in to the middle of the board, so with more than two sides they overlap
+    /// to cover the middle between them.
+    pub fn side_slab(&self, player: Player) -> (Cuboid, Transform) {
+        let reach = self.side_reach();
+        let slab = Cuboid::new(2.0 * reach.x, Self::SLAB_THICKNESS, reach.y);
+        let facing = self.side_facing(player);
+        let middle = facing * Vec3::new(0.0, -Self::SLAB_THICKNESS / 2.0, -reach.y / 2.0);
+        let transform = Transform::from_translation(middle).with_rotation(facing);
+        (slab, transform)
+    }
+
+    /// The rim standing around the bucket at `index` on a board built from pieces, and where it
+    /// goes. Stores are drawn out across their side, so they hold more than a hole.
+    pub fn rim(&self, index: Index) -> (Extrusion<Annulus>, Transform) {
+        let (Index::Player(player, _) | Index::Score(player)) = index;
+        let cup = self.cup(index);
+        let ring = Annulus::new(cup.x, cup.x + Self::RIM_WIDTH);
+        let mut position = self.bucket_position(index);
+        position.y = Self::RIM_HEIGHT / 2.0;
+        // The ring is drawn flat, so it's stood up before it's drawn out across the side.
+        let rotation = self.side_facing(player) * Quat::from_rotation_x(FRAC_PI_2);
+        let transform = Transform::from_translation(position)
+            .with_rotation(rotation)
+            .with_scale(Vec3::new(1.0, cup.y / cup.x, 1.0));
+        (Extrusion::new(ring, Self::RIM_HEIGHT), transform)
+    }
+
+    /// How far a stone can stray from the middle of the bucket at `index`, across the board, and
+    /// still be in it.
+    pub fn bucket_radius(&self, index: Index) -> f32 {
+        match index {
+            Index::Player(_, _) => Self::HOLE_RADIUS,
+            Index::Score(_) => Self::STORE_RADIUS,
+        }
+    }
+
+    /// Every stone on the board, along with the bucket it belongs in.
+    pub fn stones(&self) -> impl Iterator<Item = (Index, Entity)> + '_ {
+        self.stones
+            .iter()
+            .flat_map(|(&index, stones)| stones.iter().map(move |&stone| (index, stone)))
+    }
+
+    pub fn stones_mut(&mut self, index: Index) -> &mut Vec<Entity> {
+        self.stones.entry(index).or_default()
+    }
+
+    pub fn push_stone(&mut self, index: Index, stone: Entity) {
+        self.stones_mut(index).push(stone);
+    }
+}
 
 #[derive(Debug, Default, Clone, Copy, Resource, Deref, DerefMut)]
 pub struct Selected(Option<Index>);
@@ -109,27 +405,70 @@ impl From<PlayerTurn> for Option<usize> {
 #[derive(Debug, Default, Clone, Copy, Component, Reflect, PartialEq, Eq, Hash)]
 pub struct WinnerText;
 
-pub fn setup_state(mut commands: Commands, agent_q: Single<Entity, With<SequentialActions>>) {
+pub fn setup_state(
+    mut board: ResMut<Board>,
+    chosen: Res<ChosenVariant>,
+    resume: Option<Res<ResumeGame>>,
+    mut commands: Commands,
+    agent_q: Single<Entity, With<SequentialActions>>,
+) {
+    *board = match resume {
+        Some(resume) => {
+            commands.remove_resource::<ResumeGame>();
+            Board::new(resume.variant.clone_box())
+        }
+        None => Board::new(chosen.to_variant()),
+    };
     commands
         .actions(*agent_q)
-        .add((SpawnBoardAndPieces, NextPlayer(Player(1))));
+        .add((
+            SpawnBoardAndPieces,
+            Settle::default(),
+            NextPlayer(board.current_player()),
+        ));
+}
+
+/// Everything needed to decide whether a bucket may be picked right now.
+#[derive(SystemParam)]
+pub struct MoveSelection<'w> {
+    p_turn: Res<'w, PlayerTurn>,
+    game_state: Res<'w, State<GameState>>,
+    board: Res<'w, Board>,
+    players: Res<'w, Players>,
 }
 
-fn is_invalid_selection(
-    player: usize,
-    p_turn: Res<'_, PlayerTurn>,
-    game_state: Res<'_, State<GameState>>,
-    board: Res<'_, Board>,
-    hole: usize,
-) -> bool {
-    PlayerTurn::Player(player) != *p_turn
-        || *game_state != GameState::Playing
-        || board
-            .get_bucket_entities(Index::Player(Player(player), Hole(hole)))
-            .is_empty()
+impl MoveSelection<'_> {
+    /// Whether the player whose turn it is may sow from `index`.
+    pub fn is_legal(&self, index: Index) -> bool {
+        let Index::Player(player, _) = index else {
+            return false;
+        };
+        PlayerTurn::Player(*player) == *self.p_turn
+            && *self.game_state == GameState::Playing
+            && self.players.is_human(player)
+            && self.board.legal_moves(player).contains(&index)
+    }
+
+    /// Every hole the player whose turn it is may sow from.
+    pub fn legal_moves(&self) -> Vec<Index> {
+        let PlayerTurn::Player(player) = *self.p_turn else {
+            return Vec::new();
+        };
+        self.board
+            .legal_moves(Player(player))
+            .into_iter()
+            .filter(|&index| self.is_legal(index))
+            .collect()
+    }
 }
 
-fn setup_ui(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn setup_ui(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    board: Res<Board>,
+    undo: Res<Undo>,
+) {
+    let font = &game_assets.main_font;
     commands
         .spawn((
             Node {
@@ -144,33 +483,14 @@ fn setup_ui(mut commands: Commands, game_assets: Res<GameAssets>) {
             StateScoped(GameState::Playing),
         ))
         .with_children(|parent| {
-            parent.spawn((
-                Player(1),
-                Score,
-                Text::new("0"),
-                TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
-                TextColor(Color::Srgba(GREEN)),
-                TextLayout::new_with_justify(JustifyText::Left),
-                Node {
-                    justify_self: JustifySelf::Center,
-                    ..Default::default()
-                },
-            ));
-            parent.spawn((
-                Player(1),
-                Turn,
-                Text::new("*"),
-                TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
-                TextColor(Color::Srgba(GREEN)),
-                TextLayout::new_with_justify(JustifyText::Center),
-                Node {
-                    justify_self: JustifySelf::Center,
-                    ..Default::default()
-                },
-            ));
+            // The later players go to the left of the title, and the earlier ones to the right.
+            let players = board.player_count();
+            for player in (players / 2..players).rev() {
+                spawn_score(parent, font, Player(player), true);
+            }
             parent.spawn((
                 Text::new("Mancala: African Stones"),
-                TextFont::from_font(game_assets.main_font.clone()).with_font_size(50.0),
+                TextFont::from_font(font.clone()).with_font_size(50.0),
                 TextColor(Color::WHITE),
                 TextLayout::new_with_justify(JustifyText::Center),
                 Node {
@@ -178,30 +498,38 @@ fn setup_ui(mut commands: Commands, game_assets: Res<GameAssets>) {
                     ..Default::default()
                 },
             ));
-            parent.spawn((
-                Player(0),
-                Turn,
-                Text::new("*"),
-                TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
-                TextColor(Color::Srgba(DARK_CYAN)),
-                TextLayout::new_with_justify(JustifyText::Center),
-                Node {
-                    justify_self: JustifySelf::Center,
-                    ..Default::default()
-                },
-            ));
-            parent.spawn((
-                Player(0),
-                Score,
-                Text::new("0"),
-                TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
-                TextColor(Color::Srgba(DARK_CYAN)),
-                TextLayout::new_with_justify(JustifyText::Right),
-                Node {
-                    justify_self: JustifySelf::Center,
-                    ..Default::default()
-                },
-            ));
+            for player in (0..players / 2).rev() {
+                spawn_score(parent, font, Player(player), false);
+            }
+            if *undo == Undo::Unlimited {
+                spawn_undo_buttons(parent, &game_assets.main_font);
+            }
+            parent
+                .spawn((
+                    ViewButton,
+                    Button,
+                    Node {
+                        width: Val::Px(40.0),
+                        ..Default::default()
+                    },
+                    BackgroundColor(Color::NONE),
+                ))
+                .observe(hover_button(Color::Srgba(GOLD)))
+                .observe(unhover_button(Color::Srgba(SLATE_GRAY)))
+                .observe(
+                    |_click: Trigger<Pointer<Click>>,
+                     mut camera: Single<&mut OrbitCamera>,
+                     board: Res<Board>| {
+                        camera.cycle_view(&board);
+                    },
+                )
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("View"),
+                        TextFont::from_font(game_assets.main_font.clone()).with_font_size(20.0),
+                        TextColor(Color::Srgba(SLATE_GRAY)),
+                    ));
+                });
             parent
                 .spawn((
                     MainMenuButton,
@@ -229,6 +557,48 @@ fn setup_ui(mut commands: Commands, game_assets: Res<GameAssets>) {
         });
 }
 
+/// Spawns `player`'s score and turn marker, with the score on the outside.
+fn spawn_score(parent: &mut ChildBuilder, font: &Handle<Font>, player: Player, score_first: bool) {
+    const COLORS: [Srgba; MAX_PLAYER_COUNT] = [DARK_CYAN, GREEN, CRIMSON, ORANGE];
+
+    let color = TextColor(Color::Srgba(COLORS[*player]));
+    let score = (
+        player,
+        Score,
+        Text::new("0"),
+        TextFont::from_font(font.clone()).with_font_size(40.0),
+        color,
+        TextLayout::new_with_justify(if score_first {
+            JustifyText::Left
+        } else {
+            JustifyText::Right
+        }),
+        Node {
+            justify_self: JustifySelf::Center,
+            ..Default::default()
+        },
+    );
+    let turn = (
+        player,
+        Turn,
+        Text::new("*"),
+        TextFont::from_font(font.clone()).with_font_size(40.0),
+        color,
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            justify_self: JustifySelf::Center,
+            ..Default::default()
+        },
+    );
+    if score_first {
+        parent.spawn(score);
+        parent.spawn(turn);
+    } else {
+        parent.spawn(turn);
+        parent.spawn(score);
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, Component)]
 pub struct WinnerUi;
 
@@ -237,3 +607,7 @@ struct WinnerButton;
 
 #[derive(Debug, Default, Clone, Copy, Component)]
 struct MainMenuButton;
+
+/// Jumps the camera to the next preset view.
+#[derive(Debug, Default, Clone, Copy, Component)]
+struct ViewButton;
@@ -1,7 +1,7 @@
 use avian3d::prelude::{
     AngularDamping, AngularVelocity, Collider, ColliderConstructor, ColliderConstructorHierarchy,
     CollisionLayers, CollisionMargin, GravityScale, LinearDamping, LinearVelocity, Mass,
-    PhysicsPickable, Restitution, RigidBody, Sensor, SpeculativeMargin,
+    PhysicsPickable, Restitution, RigidBody, Sensor, Sleeping, SpeculativeMargin,
 };
 use bevy::{
     app,
@@ -10,19 +10,19 @@ use bevy::{
 };
 use bevy_mod_billboard::BillboardText;
 use bevy_sequential_actions::{Action, ActionsProxy, ModifyActions, SequentialActions, StopReason};
+use stones_rules::variants::Index;
 
 use crate::{
-    assets::GameAssets,
+    assets::{BakedColliders, GameAssets},
     game::{
-        is_invalid_selection, Board, GameState, Hole, Player, PlayerTurn, Selected, Stone,
+        preview::HoveredHole, Board, Hole, InGame, MoveSelection, Player, Selected, Stone,
         BALL_RADIUS,
     },
     physics::GameLayer,
-    rules::variants::{ChosenVariant, Index},
-    PLAYER_COUNT,
+    MAX_PLAYER_COUNT,
 };
 
-use super::turn::PlayerMove;
+use super::turn::{watch_stones_settle, BoardAtRest, Flight, PlayerMove};
 
 pub struct Plugin;
 
@@ -32,9 +32,24 @@ impl app::Plugin for Plugin {
         app.insert_resource(SetupBoard(setup_board));
         let setup_stones = app.register_system(setup_stones);
         app.insert_resource(SetupStones(setup_stones));
+        app.add_systems(
+            Update,
+            reseat_escaped_stones
+                .after(watch_stones_settle)
+                .run_if(in_state(InGame)),
+        );
     }
 }
 
+/// How far below the top of the board a stone has to be before it has fallen through.
+const FALLEN_BELOW: f32 = -0.05;
+
+/// The colour of boards built from pieces, close to the wood of the model.
+const WOOD: Color = Color::srgb(0.45, 0.28, 0.15);
+
+/// How brightly a hole is lit while it's being pointed at.
+pub const HIGHLIGHT: f32 = 500.0;
+
 #[derive(Clone, Copy, Debug, Resource, Deref)]
 struct SetupBoard(SystemId);
 
@@ -62,33 +77,78 @@ impl Action for SpawnBoardAndPieces {
     fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
 }
 
-pub fn setup_board(mut board: ResMut<Board>, mut commands: Commands, game_assets: Res<GameAssets>) {
-    *board = Board(ChosenVariant::default().to_variant());
+/// Spawns a stone for every seed on the board, onto a board that is already set up.
+pub struct SpawnPieces;
 
-    let collider = ColliderConstructorHierarchy::new(ColliderConstructor::TrimeshFromMesh);
+impl Action for SpawnPieces {
+    fn is_finished(&self, _agent: Entity, _world: &World) -> bool {
+        true
+    }
 
-    commands.spawn((
+    fn on_start(&mut self, _agent: Entity, world: &mut World) -> bool {
+        world
+            .run_system(world.get_resource::<SetupStones>().unwrap().0)
+            .unwrap();
+
+        true
+    }
+
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
+}
+
+pub fn setup_board(
+    board: Res<Board>,
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    baked: Res<Assets<BakedColliders>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut board_entity = commands.spawn((
         RigidBody::Static,
-        collider,
         CollisionMargin(0.005),
         CollisionLayers::new(GameLayer::PhysicsObject, GameLayer::PhysicsObject),
         Restitution::new(0.0),
         Name::from("Board"),
-        SceneRoot::from(game_assets.board_scene.clone()),
-        StateScoped(GameState::Playing),
+        Transform::default(),
+        Visibility::default(),
+        StateScoped(InGame),
     ));
+    if board.fits_model() {
+        board_entity.insert(SceneRoot::from(game_assets.board_scene.clone()));
+        // Working the colliders out from the meshes is slow, so only do it if they weren't baked.
+        let baked = baked.get(&game_assets.baked_colliders);
+        match baked.and_then(BakedColliders::board) {
+            Some(collider) => board_entity.insert(collider),
+            None => board_entity.insert(ColliderConstructorHierarchy::new(
+                ColliderConstructor::TrimeshFromMesh,
+            )),
+        };
+    } else {
+        let material = materials.add(StandardMaterial {
+            base_color: WOOD,
+            perceptual_roughness: 0.8,
+            ..Default::default()
+        });
+        board_entity.with_children(|parent| {
+            build_board(parent, &board, &mut meshes, material);
+        });
+    }
+
+    const COLORS: [Color; MAX_PLAYER_COUNT] = [
+        Color::linear_rgba(0.0, 0.0, 1.0, 1.0),
+        Color::linear_rgba(0.0, 1.0, 0.0, 1.0),
+        Color::linear_rgba(1.0, 0.0, 0.0, 1.0),
+        Color::linear_rgba(1.0, 0.5, 0.0, 1.0),
+    ];
 
-    for player in 0..PLAYER_COUNT {
-        for hole in 0..board.hole_count() {
+    for player in 0..board.player_count() {
+        for hole in 0..board.position().side_hole_count() {
+            let index = Index::Player(Player(player), Hole(hole));
             // Invisible material for hole
-            let mut bucket_position = board
-                .bucket_position(Index::Player(Player(player), Hole(hole)))
-                + Vec3::new(0.0, 5.0, 0.0);
+            let mut bucket_position = board.bucket_position(index);
             bucket_position.y = 0.01;
-            let color = match player {
-                0 => Color::linear_rgba(0.0, 0.0, 1.0, 1.0),
-                _ => Color::linear_rgba(0.0, 1.0, 0.0, 1.0),
-            };
+            let color = COLORS[player];
             commands
                 .spawn((
                     Name::from(format!("bucket_{player}_{hole}")),
@@ -106,52 +166,53 @@ pub fn setup_board(mut board: ResMut<Board>, mut commands: Commands, game_assets
                     CollisionLayers::new(GameLayer::MouseObject, GameLayer::MouseObject),
                     Sensor,
                     PhysicsPickable,
-                    StateScoped(GameState::Playing),
+                    StateScoped(InGame),
                 ))
                 .observe(
                     move |over: Trigger<Pointer<Over>>,
                           mut lights: Query<&mut PointLight>,
-                          turn: Res<PlayerTurn>,
-                          board: Res<Board>,
-                          game_state: Res<State<GameState>>| {
+                          mut hovered: ResMut<HoveredHole>,
+                          selection: MoveSelection| {
                         let entity = over.entity();
                         let mut light = lights.get_mut(entity).unwrap();
-                        if is_invalid_selection(player, turn, game_state, board, hole) {
+                        if !selection.is_legal(index) {
                             light.intensity = 0.0;
                             return;
                         }
-                        light.intensity = 500.0;
+                        light.intensity = HIGHLIGHT;
+                        **hovered = Some(index);
                     },
                 )
                 .observe(
-                    |out: Trigger<Pointer<Out>>, mut lights: Query<&mut PointLight>| {
+                    move |out: Trigger<Pointer<Out>>,
+                          mut lights: Query<&mut PointLight>,
+                          mut hovered: ResMut<HoveredHole>| {
                         let entity = out.entity();
                         let mut light = lights.get_mut(entity).unwrap();
                         light.intensity = 0.0;
+                        if **hovered == Some(index) {
+                            **hovered = None;
+                        }
                     },
                 )
                 .observe(
                     move |_down: Trigger<Pointer<Down>>,
                           mut selected: ResMut<Selected>,
-                          turn: Res<PlayerTurn>,
-                          board: Res<Board>,
-                          game_state: Res<State<GameState>>| {
-                        if is_invalid_selection(player, turn, game_state, board, hole) {
+                          selection: MoveSelection| {
+                        if !selection.is_legal(index) {
                             return;
                         }
 
-                        selected.0 = Some(Index::Player(Player(player), Hole(hole)));
+                        selected.0 = Some(index);
                     },
                 )
                 .observe(
                     move |_up: Trigger<Pointer<Up>>,
                           mut selected: ResMut<Selected>,
-                          turn: Res<PlayerTurn>,
-                          board: Res<Board>,
-                          game_state: Res<State<GameState>>,
+                          selection: MoveSelection,
                           agent: Single<Entity, With<SequentialActions>>,
                           mut commands: Commands| {
-                        if is_invalid_selection(player, turn, game_state, board, hole) {
+                        if !selection.is_legal(index) {
                             return;
                         }
 
@@ -159,7 +220,7 @@ pub fn setup_board(mut board: ResMut<Board>, mut commands: Commands, game_assets
                             let Some(selected) = selected.0.as_mut() else {
                                 return;
                             };
-                            if *selected != Index::Player(Player(player), Hole(hole)) {
+                            if *selected != index {
                                 return;
                             }
                             commands
@@ -170,86 +231,156 @@ pub fn setup_board(mut board: ResMut<Board>, mut commands: Commands, game_assets
                     },
                 );
 
-            let offset = match player {
-                0 => Vec3::new(0.0, 0.0, -0.1),
-                _ => Vec3::new(0.0, 0.0, 0.1),
-            };
-            let transform = Transform::from_translation(bucket_position + offset)
+            let transform = Transform::from_translation(board.label_position(index))
                 .with_scale(Vec3::splat(0.001));
             commands.spawn((
                 Name::from(format!("bucket_label_{player}_{hole}")),
                 Player(player),
                 Hole(hole),
-                BillboardText::new("4"),
+                BillboardText::new(board.starting_pieces().to_string()),
                 TextLayout::new_with_justify(JustifyText::Center),
                 TextFont::from_font(game_assets.main_font.clone()).with_font_size(30.0),
                 TextColor(Color::WHITE),
                 transform,
-                StateScoped(GameState::Playing),
+                StateScoped(InGame),
             ));
         }
     }
 }
 
+/// Builds a board the model doesn't fit out of pieces: a slab under each side, with a rim standing
+/// around every hole and store.
+fn build_board(
+    parent: &mut ChildBuilder,
+    board: &Board,
+    meshes: &mut Assets<Mesh>,
+    material: Handle<StandardMaterial>,
+) {
+    for player in 0..board.player_count() {
+        let (slab, transform) = board.side_slab(Player(player));
+        let size = 2.0 * slab.half_size;
+        parent.spawn((
+            Name::from(format!("board_side_{player}")),
+            Mesh3d(meshes.add(slab)),
+            MeshMaterial3d(material.clone()),
+            transform,
+            Collider::cuboid(size.x, size.y, size.z),
+            CollisionLayers::new(GameLayer::PhysicsObject, GameLayer::PhysicsObject),
+        ));
+    }
+    for index in board.buckets() {
+        let (rim, transform) = board.rim(index);
+        parent.spawn((
+            Name::from(format!("board_rim_{index}")),
+            Mesh3d(meshes.add(rim)),
+            MeshMaterial3d(material.clone()),
+            transform,
+            ColliderConstructor::TrimeshFromMesh,
+            CollisionLayers::new(GameLayer::PhysicsObject, GameLayer::PhysicsObject),
+        ));
+    }
+}
+
 pub fn setup_stones(
     mut commands: Commands,
     mut board: ResMut<Board>,
     game_assets: Res<GameAssets>,
     meshes: Res<Assets<Mesh>>,
+    baked: Res<Assets<BakedColliders>>,
 ) {
     const SCALE: f32 = 0.8;
 
     let mut materials = game_assets.stone_materials.iter().cycle().cloned();
 
-    let mesh = meshes.get(&game_assets.stone_mesh).unwrap();
-    let collider = Collider::convex_hull_from_mesh(mesh).unwrap();
+    let baked = baked.get(&game_assets.baked_colliders);
+    let collider = baked.and_then(BakedColliders::stone).unwrap_or_else(|| {
+        let mesh = meshes.get(&game_assets.stone_mesh).unwrap();
+        Collider::convex_hull_from_mesh(mesh).unwrap()
+    });
 
     tracing::info!("Spawning stones");
-    for player in 0..PLAYER_COUNT {
-        for hole in 0..board.hole_count() {
-            for i in 0..board.starting_pieces() {
-                let position = board.bucket_position(Index::Player(Player(player), Hole(hole)));
-                let perturb = Vec3::new(
-                    (i as f32 * 0.001).sin() * 0.0025,
-                    i as f32 * BALL_RADIUS,
-                    (i as f32 * 0.001).cos() * 0.0025,
-                );
+    // A resumed game may already have seeds in the stores.
+    for index in board.buckets().collect::<Vec<_>>() {
+        let (player, bucket) = match index {
+            Index::Player(Player(player), Hole(hole)) => (player, hole.to_string()),
+            Index::Score(Player(player)) => (player, "store".to_string()),
+        };
+        for i in 0..board.seeds(index) {
+            let position = board.bucket_position(index);
+            let perturb = Vec3::new(
+                (i as f32 * 0.001).sin() * 0.0025,
+                i as f32 * BALL_RADIUS,
+                (i as f32 * 0.001).cos() * 0.0025,
+            );
 
-                board.push_entity(
-                    Index::Player(Player(player), Hole(hole)),
-                    commands
-                        .spawn((
-                            Name::from(format!("stone_{player}_{hole}_{i}")),
-                            Stone,
-                            RigidBody::Dynamic,
-                            collider.clone(),
-                            CollisionMargin(0.0025),
-                            CollisionLayers::new(
-                                GameLayer::PhysicsObject,
-                                GameLayer::PhysicsObject,
-                            ),
-                            // Physics
-                            (
-                                GravityScale(0.25),
-                                Mass(0.0001),
-                                LinearVelocity(Vec3::ZERO),
-                                AngularVelocity(Vec3::ZERO),
-                                Restitution::new(0.00),
-                                LinearDamping(0.9999),
-                                AngularDamping(100.0),
-                                Mesh3d(game_assets.stone_mesh.clone()),
-                                MeshMaterial3d(materials.next().expect("cycles")),
-                                Transform::from_translation(position + perturb)
-                                    .with_rotation(Quat::from_rotation_x(90.0))
-                                    .with_scale(Vec3::splat(SCALE)),
-                                SpeculativeMargin(0.005),
-                                // Maybe we'll turn this back on, but speculative is doing great.
-                            ),
-                            StateScoped(GameState::Playing),
-                        ))
-                        .id(),
-                );
-            }
+            board.push_stone(
+                index,
+                commands
+                    .spawn((
+                        Name::from(format!("stone_{player}_{bucket}_{i}")),
+                        Stone,
+                        RigidBody::Dynamic,
+                        collider.clone(),
+                        CollisionMargin(0.0025),
+                        CollisionLayers::new(GameLayer::PhysicsObject, GameLayer::PhysicsObject),
+                        // Physics
+                        (
+                            GravityScale(0.25),
+                            Mass(0.0001),
+                            LinearVelocity(Vec3::ZERO),
+                            AngularVelocity(Vec3::ZERO),
+                            Restitution::new(0.00),
+                            LinearDamping(0.9999),
+                            AngularDamping(100.0),
+                            Mesh3d(game_assets.stone_mesh.clone()),
+                            MeshMaterial3d(materials.next().expect("cycles")),
+                            Transform::from_translation(position + perturb)
+                                .with_rotation(Quat::from_rotation_x(90.0))
+                                .with_scale(Vec3::splat(SCALE)),
+                            SpeculativeMargin(0.005),
+                            // Maybe we'll turn this back on, but speculative is doing great.
+                        ),
+                        StateScoped(InGame),
+                    ))
+                    .id(),
+            );
         }
     }
 }
+
+/// Drops any stone that has bounced out of its bucket, or fallen through the board, back into the
+/// bucket the rules say it is in.
+fn reseat_escaped_stones(
+    board: Res<Board>,
+    at_rest: Res<BoardAtRest>,
+    mut stones: Query<
+        (
+            &Name,
+            &mut Transform,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+        ),
+        (With<Stone>, Without<Flight>),
+    >,
+    mut commands: Commands,
+) {
+    for (index, stone) in board.stones() {
+        let Ok((name, mut transform, mut linear, mut angular)) = stones.get_mut(stone) else {
+            continue;
+        };
+        let bucket = board.bucket_position(index);
+        let fallen = transform.translation.y < FALLEN_BELOW;
+        // Stones still rolling around may yet settle into the right bucket.
+        let strayed = at_rest.is_some()
+            && transform.translation.xz().distance(bucket.xz()) > board.bucket_radius(index);
+        if !fallen && !strayed {
+            continue;
+        }
+
+        tracing::warn!("{name} escaped from {index}, putting it back");
+        transform.translation = bucket;
+        **linear = Vec3::ZERO;
+        **angular = Vec3::ZERO;
+        commands.entity(stone).remove::<Sleeping>();
+    }
+}
@@ -2,20 +2,18 @@ use std::marker::PhantomData;
 
 use bevy::ecs::system::SystemId;
 use bevy::{app, prelude::*};
-use bevy_sequential_actions::{
-    Action, ActionsProxy, AddOrder, BoxedAction, DropReason,
-    ModifyActions, SequentialActions, StopReason,
-};
+use bevy_sequential_actions::{Action, SequentialActions, StopReason};
 
 pub mod board;
 pub mod turn;
 pub mod ui;
+pub mod undo;
 
 pub struct Plugin;
 
 impl app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((board::Plugin, turn::Plugin, ui::Plugin))
+        app.add_plugins((board::Plugin, turn::Plugin, ui::Plugin, undo::Plugin))
             .add_systems(Startup, spawn_agent);
     }
 }
@@ -114,70 +112,23 @@ where
     fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
 }
 
-/// An `Action` that will run the given array of systems in sequence.
-///
-/// Specifically useful for Adding a `Wait` action directly after the `MovePiece` action for instance.
-#[derive(Debug)]
-pub struct ChainActions<const N: usize> {
-    actions: [BoxedAction; N],
-    index: usize,
-    canceled: bool,
-}
-
-impl<const N: usize> ChainActions<N> {
-    pub fn new(actions: [BoxedAction; N]) -> Self {
-        Self {
-            actions,
-            index: 0,
-            canceled: false,
-        }
-    }
-}
-
-impl<const N: usize> Action for ChainActions<N> {
-    fn is_finished(&self, agent: Entity, world: &World) -> bool {
-        self.actions[self.index].is_finished(agent, world)
-    }
-
-    fn on_add(&mut self, agent: Entity, world: &mut World) {
-        self.actions
-            .iter_mut()
-            .for_each(|action| action.on_add(agent, world));
-    }
+/// An `Action` that sends an event, so it goes out in step with the actions around it.
+#[derive(Clone, Copy, Debug)]
+pub struct SendEvent<E: Event>(pub E);
 
-    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
-        self.actions[self.index].on_start(agent, world)
+impl<E> Action for SendEvent<E>
+where
+    E: Event + Clone,
+{
+    fn is_finished(&self, _agent: Entity, _world: &World) -> bool {
+        true
     }
 
-    fn on_stop(&mut self, agent: Option<Entity>, world: &mut World, reason: StopReason) {
-        self.actions[self.index].on_stop(agent, world, reason);
-        self.canceled = reason == StopReason::Canceled;
-    }
+    fn on_start(&mut self, _agent: Entity, world: &mut World) -> bool {
+        world.send_event(self.0.clone());
 
-    fn on_remove(&mut self, agent: Option<Entity>, world: &mut World) {
-        self.actions[self.index].on_remove(agent, world);
+        true
     }
 
-    fn on_drop(mut self: Box<Self>, agent: Option<Entity>, world: &mut World, reason: DropReason) {
-        self.index += 1;
-
-        if self.index >= N {
-            return;
-        }
-
-        if self.canceled || reason != DropReason::Done {
-            self.actions
-                .iter_mut()
-                .for_each(|action| action.on_remove(agent, world));
-            return;
-        }
-
-        let Some(agent) = agent else { return };
-
-        world
-            .actions(agent)
-            .start(false)
-            .order(AddOrder::Front)
-            .add(self as BoxedAction);
-    }
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
 }
@@ -1,25 +1,35 @@
 use std::time::Duration;
 
-use avian3d::prelude::{AngularVelocity, LinearVelocity, Sleeping};
+use avian3d::prelude::{AngularVelocity, LinearVelocity, RigidBody, Sleeping};
 use bevy::{app, ecs::system::SystemId, prelude::*};
 use bevy_sequential_actions::{
-    actions, Action, ActionsProxy, ModifyActions, SequentialActions, StopReason,
+    Action, ActionsProxy, BoxedAction, ModifyActions, SequentialActions, StopReason,
 };
+use stones_rules::variants::{Index, MoveEvent};
 
 use crate::{
-    game::{Board, Player, PlayerTurn, Stone},
-    rules::variants::Index,
+    events::{
+        ExtraTurn, MoveStarted, SeedSown, SeedsCaptured, SeedsPickedUp, SeedsSwept, TurnChanged,
+    },
+    game::{ai::start_thinking, save::save_game, Board, InGame, Player, PlayerTurn, Stone},
 };
 
-use super::{ui::UpdateLabels, ChainActions, RunSystem, SystemInResource};
+use super::{
+    ui::{DeclareWinner, UpdateLabels},
+    undo::UndoneMoves,
+    RunSystem, SendEvent, SystemInResource,
+};
 
 pub struct Plugin;
 
 impl app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         let perform_move = app.register_system(perform_move);
-        app.insert_resource(PlayerMoveResource(perform_move));
-        app.add_systems(Update, update_wait_timer);
+        app.insert_resource(PlayerMoveResource(perform_move))
+            .insert_resource(SowingArc::default());
+        app.insert_resource(BoardAtRest::default())
+            .add_systems(OnExit(InGame), calm_board)
+            .add_systems(Update, (update_wait_timer, fly_stones, watch_stones_settle));
     }
 }
 
@@ -41,8 +51,11 @@ pub fn perform_move(
     index: In<Index>,
     mut board: ResMut<Board>,
     mut p_turn: ResMut<PlayerTurn>,
+    mut undone: ResMut<UndoneMoves>,
+    mut at_rest: ResMut<BoardAtRest>,
     mut lights: Query<&mut PointLight>,
     agent: Single<Entity, With<SequentialActions>>,
+    mut started: EventWriter<MoveStarted>,
     mut commands: Commands,
 ) {
     let PlayerTurn::Player(turn) = *p_turn else {
@@ -50,8 +63,32 @@ pub fn perform_move(
     };
 
     *p_turn = PlayerTurn::None;
+    // Replaying the move that was taken back is a redo, anything else starts a new line of play.
+    if undone.last() == Some(&*index) {
+        undone.pop();
+    } else {
+        undone.clear();
+    }
 
-    let actions = board.perform_move(*index, Player(turn));
+    debug_assert_eq!(board.current_player(), Player(turn));
+    started.send(MoveStarted {
+        player: Player(turn),
+        from: *index,
+    });
+    let events = board.perform_move(*index);
+    let mut actions = animate_move(&mut board, &events);
+    actions.push(Box::new(Settle::default()));
+    at_rest.disturb();
+    match board.outcome() {
+        Some(outcome) => actions.push(Box::new(DeclareWinner::with_input(outcome))),
+        None => {
+            let next = board.current_player();
+            if next == Player(turn) {
+                actions.push(Box::new(SendEvent(ExtraTurn { player: next })));
+            }
+            actions.push(Box::new(NextPlayer(next)));
+        }
+    }
     // Make all the lights go out for now.
     lights.par_iter_mut().for_each(|mut light| {
         light.intensity = 0.0;
@@ -63,6 +100,94 @@ pub fn perform_move(
         .add(actions);
 }
 
+/// Turns the events of a move into the actions that animate it, moving the stones in `board` to
+/// match. The seed events are sent as the stones they describe land.
+pub fn animate_move(board: &mut Board, events: &[MoveEvent]) -> Vec<BoxedAction> {
+    let mut hand = Vec::new();
+    let mut actions = Vec::<BoxedAction>::new();
+
+    for event in events {
+        match *event {
+            MoveEvent::PickUp { from, count } => {
+                let stones = board.stones_mut(from);
+                let start = stones.len() - count;
+                let picked = stones.drain(start..).rev().collect::<Vec<_>>();
+                // A relay sowing picks up the hole its last seed is still flying into.
+                actions.push(Box::new(AwaitLanding(picked.clone())));
+                actions.push(Box::new(SendEvent(SeedsPickedUp { from, count })));
+                hand.extend(picked);
+            }
+            MoveEvent::Sow { to } => {
+                let stone = hand.pop().expect("Sowing from an empty hand");
+                board.push_stone(to, stone);
+                let filled = board.stones_mut(to).len();
+                actions.push(Box::new(
+                    MovePiece::new(stone, board.bucket_position(to))
+                        .sowing(SeedSown { to, filled }),
+                ));
+            }
+            MoveEvent::Capture { from, to, count } => {
+                let stones = board.stones_mut(from);
+                let captured = stones.split_off(stones.len() - count);
+                let destination = board.bucket_position(to);
+                // The seed that made the capture may still be on its way into the hole.
+                actions.push(Box::new(AwaitLanding(captured.clone())));
+                actions.push(Box::new(MovePieces::new(captured.clone(), destination)));
+                actions.push(Box::new(SendEvent(SeedsCaptured { from, to, count })));
+                board.stones_mut(to).extend(captured);
+            }
+            MoveEvent::Sweep { from, to, count } => {
+                let stones = board.stones_mut(from);
+                let swept = stones.split_off(stones.len() - count);
+                let destination = board.bucket_position(to);
+                // The last seed sown may have landed in a hole that is swept.
+                actions.push(Box::new(AwaitLanding(swept.clone())));
+                actions.push(Box::new(MovePieces::new(swept.clone(), destination)));
+                actions.push(Box::new(SendEvent(SeedsSwept { from, to, count })));
+                board.stones_mut(to).extend(swept);
+            }
+        }
+    }
+
+    actions
+}
+
+/// Turns the events of a move that was just taken back into the actions that return every stone
+/// to where it came from, moving the stones in `board` to match.
+pub fn animate_undo(board: &mut Board, events: &[MoveEvent]) -> Vec<BoxedAction> {
+    let mut hand = Vec::new();
+    let mut actions = Vec::<BoxedAction>::new();
+
+    for event in events.iter().rev() {
+        match *event {
+            MoveEvent::Capture { from, to, count } | MoveEvent::Sweep { from, to, count } => {
+                let stones = board.stones_mut(to);
+                let captured = stones.split_off(stones.len() - count);
+                let destination = board.bucket_position(from);
+                actions.push(Box::new(MovePieces::new(captured.clone(), destination)));
+                board.stones_mut(from).extend(captured);
+            }
+            MoveEvent::Sow { to } => {
+                let stone = board
+                    .stones_mut(to)
+                    .pop()
+                    .expect("Taking back a seed that was never sown");
+                hand.push(stone);
+            }
+            MoveEvent::PickUp { from, .. } => {
+                let destination = board.bucket_position(from);
+                let returned = hand.drain(..).rev().collect::<Vec<_>>();
+                // Some of them may have only just been carried back out of a store.
+                actions.push(Box::new(AwaitLanding(returned.clone())));
+                actions.push(Box::new(MovePieces::new(returned.clone(), destination)));
+                board.stones_mut(from).extend(returned);
+            }
+        }
+    }
+
+    actions
+}
+
 #[derive(Debug, Clone, Component, Deref, DerefMut)]
 pub struct WaitTimer {
     pub timer: Timer,
@@ -121,34 +246,261 @@ impl Action for Wait {
     }
 }
 
+/// How stones travel from one bucket to the next.
+#[derive(Debug, Clone, Copy, Resource)]
+pub struct SowingArc {
+    /// How long each stone takes to land.
+    pub duration: Duration,
+    /// How far above the straight line between the buckets each stone rises.
+    pub height: f32,
+    /// How far through its flight each sown stone is when the next one sets off, from `0.0` for
+    /// all at once to `1.0` for one after another.
+    pub spacing: f32,
+}
+
+impl Default for SowingArc {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs_f32(0.25),
+            height: 0.05,
+            spacing: 0.3,
+        }
+    }
+}
+
+/// A stone on its way to another bucket, carried along its arc instead of by the physics.
+#[derive(Debug, Clone, Component)]
+pub struct Flight {
+    from: Vec3,
+    to: Vec3,
+    height: f32,
+    timer: Timer,
+    /// Sent once the stone lands, if it was sown.
+    sown: Option<SeedSown>,
+}
+
+impl Flight {
+    /// Where the stone should be once `fraction` of the flight is over.
+    fn position(&self, fraction: f32) -> Vec3 {
+        let rise = 4.0 * fraction * (1.0 - fraction);
+        self.from.lerp(self.to, fraction) + Vec3::Y * self.height * rise
+    }
+}
+
+fn fly_stones(
+    mut stones: Query<(Entity, &mut Flight, &mut Transform)>,
+    time: Res<Time>,
+    mut sown: EventWriter<SeedSown>,
+    mut commands: Commands,
+) {
+    for (stone, mut flight, mut transform) in &mut stones {
+        flight.timer.tick(time.delta());
+        if !flight.timer.finished() {
+            transform.translation = flight.position(flight.timer.fraction());
+            continue;
+        }
+
+        // Landed, so the physics can settle it into the bucket.
+        if let Some(event) = flight.sown {
+            sown.send(event);
+        }
+        transform.translation = flight.to;
+        transform.rotation = Quat::from_rotation_x(90.0_f32.to_radians());
+        commands.entity(stone).remove::<Flight>().insert((
+            RigidBody::Dynamic,
+            LinearVelocity(Vec3::ZERO),
+            AngularVelocity(Vec3::ZERO),
+        ));
+    }
+}
+
+/// Hands `stone` over from the physics to a [`Flight`] to the bucket at `destination`.
+fn launch(world: &mut World, stone: Entity, destination: Vec3, sown: Option<SeedSown>) {
+    let arc = *world.resource::<SowingArc>();
+    let mut stone = world.entity_mut(stone);
+    let from = stone.get::<Transform>().unwrap().translation;
+    // The arc moves the stone, so the physics has to let go of it until it lands.
+    stone.remove::<Sleeping>().insert((
+        RigidBody::Kinematic,
+        LinearVelocity(Vec3::ZERO),
+        AngularVelocity(Vec3::ZERO),
+        Flight {
+            from,
+            to: destination,
+            height: arc.height,
+            timer: Timer::new(arc.duration, TimerMode::Once),
+            sown,
+        },
+    ));
+}
+
+/// Carries a stone along an arc to the bucket at `destination`, finishing once it is
+/// [`SowingArc::spacing`] of the way there so the next stone can follow it.
 #[derive(Debug, Clone, Copy)]
 pub struct MovePiece {
     stone: Entity,
     destination: Vec3,
+    sown: Option<SeedSown>,
 }
 
 impl MovePiece {
-    pub fn new_action(stone: Entity, destination: Vec3) -> ChainActions<2> {
-        ChainActions::new(actions![Self { stone, destination }, Wait::from_secs(0.1)])
+    pub fn new(stone: Entity, destination: Vec3) -> Self {
+        Self {
+            stone,
+            destination,
+            sown: None,
+        }
+    }
+
+    /// Sends `sown` as the stone lands.
+    pub fn sowing(mut self, sown: SeedSown) -> Self {
+        self.sown = Some(sown);
+        self
     }
 }
 
 impl Action for MovePiece {
-    fn is_finished(&self, _agent: Entity, _world: &World) -> bool {
-        true
+    fn is_finished(&self, _agent: Entity, world: &World) -> bool {
+        let spacing = world.resource::<SowingArc>().spacing;
+        world
+            .get::<Flight>(self.stone)
+            .is_none_or(|flight| flight.timer.fraction() >= spacing)
     }
 
-    fn on_start(&mut self, _agent: Entity, world: &mut World) -> bool {
-        let mut query =
-            QueryState::<(&mut Transform, &mut LinearVelocity, &mut AngularVelocity)>::new(world);
-        let (mut transform, mut linear_velocity, mut angular_veocity) =
-            query.get_mut(world, self.stone).unwrap();
-        transform.translation = self.destination;
-        transform.rotation = Quat::from_rotation_x(90.0_f32.to_radians());
-        **linear_velocity = Vec3::ZERO;
-        **angular_veocity = Vec3::ZERO;
+    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
+        launch(world, self.stone, self.destination, self.sown);
+        self.is_finished(agent, world)
+    }
 
-        true
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
+}
+
+/// Carries every one of `stones` to the bucket at `destination` at once, finishing once they have
+/// all landed.
+#[derive(Debug, Clone)]
+pub struct MovePieces {
+    stones: Vec<Entity>,
+    destination: Vec3,
+}
+
+impl MovePieces {
+    pub fn new(stones: Vec<Entity>, destination: Vec3) -> Self {
+        Self {
+            stones,
+            destination,
+        }
+    }
+}
+
+impl Action for MovePieces {
+    fn is_finished(&self, _agent: Entity, world: &World) -> bool {
+        self.stones
+            .iter()
+            .all(|&stone| world.get::<Flight>(stone).is_none())
+    }
+
+    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
+        for &stone in &self.stones {
+            launch(world, stone, self.destination, None);
+        }
+        self.is_finished(agent, world)
+    }
+
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
+}
+
+/// Waits for any of `stones` still flying from earlier in the move to land.
+#[derive(Debug, Clone)]
+pub struct AwaitLanding(pub Vec<Entity>);
+
+impl Action for AwaitLanding {
+    fn is_finished(&self, _agent: Entity, world: &World) -> bool {
+        self.0
+            .iter()
+            .all(|&stone| world.get::<Flight>(stone).is_none())
+    }
+
+    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
+        self.is_finished(agent, world)
+    }
+
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
+}
+
+/// How slowly a stone may still be moving, in metres a second, and count as at rest.
+const SETTLED_SPEED: f32 = 0.01;
+
+/// How slowly a stone may still be spinning, in radians a second, and count as at rest.
+const SETTLED_SPIN: f32 = 0.1;
+
+/// How long every stone has to stay at rest before the board counts as settled.
+const SETTLED_FOR: Duration = Duration::from_millis(100);
+
+/// The longest to wait for the board to settle, in case a stone never stops rolling.
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// When every stone last came to rest, or `None` while any of them are still moving.
+#[derive(Debug, Default, Clone, Copy, Resource, Deref)]
+pub struct BoardAtRest {
+    #[deref]
+    since: Option<Duration>,
+    /// Whether a move is still being animated, up until its [`Settle`] starts.
+    animating: bool,
+}
+
+impl BoardAtRest {
+    /// Counts the board as moving until the animation just queued has played out.
+    ///
+    /// The [`Board`] already has every stone in the bucket it ends up in, but they set off one
+    /// after another, so the ones waiting their turn are out of place without moving at all.
+    pub fn disturb(&mut self) {
+        self.since = None;
+        self.animating = true;
+    }
+}
+
+/// Forgets any animation that was cut short by leaving the board.
+fn calm_board(mut at_rest: ResMut<BoardAtRest>) {
+    *at_rest = BoardAtRest::default();
+}
+
+pub fn watch_stones_settle(
+    stones: Query<(&LinearVelocity, &AngularVelocity, Has<Flight>), With<Stone>>,
+    time: Res<Time>,
+    mut at_rest: ResMut<BoardAtRest>,
+) {
+    let still = !at_rest.animating
+        && stones.iter().all(|(linear, angular, flying)| {
+            !flying && linear.length() < SETTLED_SPEED && angular.length() < SETTLED_SPIN
+        });
+    match (still, at_rest.since) {
+        (true, None) => at_rest.since = Some(time.elapsed()),
+        (false, Some(_)) => at_rest.since = None,
+        _ => {}
+    }
+}
+
+/// Waits until every stone has come to rest, or gives up after [`SETTLE_TIMEOUT`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Settle {
+    started: Duration,
+}
+
+impl Action for Settle {
+    fn is_finished(&self, _agent: Entity, world: &World) -> bool {
+        let now = world.resource::<Time>().elapsed();
+        // Stones that were still before we started waiting may not have been dropped yet.
+        let settled = world
+            .resource::<BoardAtRest>()
+            .is_some_and(|since| now.saturating_sub(since.max(self.started)) >= SETTLED_FOR);
+        settled || now.saturating_sub(self.started) >= SETTLE_TIMEOUT
+    }
+
+    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
+        self.started = world.resource::<Time>().elapsed();
+        // Every stone has flown, so from here on the physics alone decides when they are still.
+        world.resource_mut::<BoardAtRest>().animating = false;
+        self.is_finished(agent, world)
     }
 
     fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
@@ -164,12 +516,14 @@ impl Action for NextPlayer {
     fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
         let mut p_turn = world.resource_mut::<PlayerTurn>();
         *p_turn = PlayerTurn::Player(*self.0);
+        world.send_event(TurnChanged { player: self.0 });
+        save_game(world);
+        start_thinking(world, self.0);
 
-        world.actions(agent).start(false).add((
-            UpdateLabels::new(),
-            Wait::from_secs(0.5),
-            SleepPieces,
-        ));
+        world
+            .actions(agent)
+            .start(false)
+            .add((UpdateLabels::new(), SleepPieces));
 
         true
     }
@@ -177,6 +531,8 @@ impl Action for NextPlayer {
     fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
 }
 
+/// Puts the stones that have come to rest to sleep, leaving any still rolling after a [`Settle`]
+/// gave up on them to finish under the physics.
 pub struct SleepPieces;
 
 impl Action for SleepPieces {
@@ -185,9 +541,16 @@ impl Action for SleepPieces {
     }
 
     fn on_start(&mut self, _agent: Entity, world: &mut World) -> bool {
-        let stones = world
-            .query_filtered::<Entity, With<Stone>>()
+        let mut stones = world.query_filtered::<
+            (Entity, &LinearVelocity, &AngularVelocity),
+            (With<Stone>, Without<Flight>),
+        >();
+        let stones = stones
             .iter(world)
+            .filter(|(_, linear, angular)| {
+                linear.length() < SETTLED_SPEED && angular.length() < SETTLED_SPIN
+            })
+            .map(|(stone, _, _)| stone)
             .collect::<Vec<_>>();
         stones.into_iter().for_each(|stone| {
             let mut stone = world.entity_mut(stone);
@@ -1,15 +1,26 @@
-use bevy::color::palettes::css::{GREEN, LIGHT_CYAN};
+use bevy::color::palettes::css::{CRIMSON, GOLD, GREEN, LIGHT_CYAN, ORANGE};
 use bevy::ecs::query::QueryData;
 use bevy::ui::FocusPolicy;
 use bevy::{app, ecs::system::SystemId, prelude::*};
 use bevy_mod_billboard::BillboardText;
+use bevy_prefs_lite::Preferences;
 use bevy_sequential_actions::{ActionsProxy, ModifyActions, SequentialActions};
+use stones_rules::{
+    notation::format_moves,
+    variants::{ChosenVariant, Index, Outcome},
+};
 
 use crate::assets::GameAssets;
+use crate::events::GameOver;
 use crate::game::{
+    ai::Players,
+    record::{Format, GameRecord, GameStarted},
+    save::clear_saved_game,
     Board, GameState, Hole, Player, PlayerTurn, Score, Turn, WinnerButton, WinnerText,
+    PLAYER_NAMES,
 };
-use crate::rules::variants::Index;
+use crate::ui::{hover_button, unhover_button};
+use crate::MAX_PLAYER_COUNT;
 
 use super::turn::SleepPieces;
 use super::{RunSystem, SystemInResource};
@@ -67,9 +78,7 @@ fn update_labels(
              hole,
              mut text,
          }| {
-            let count = board
-                .get_bucket_entities(Index::Player(*player, *hole))
-                .len();
+            let count = board.seeds(Index::Player(*player, *hole));
             text.0 = count.to_string();
         },
     );
@@ -77,7 +86,7 @@ fn update_labels(
     score
         .par_iter_mut()
         .for_each(|TextDataItem { player, mut text }| {
-            let count = board.get_bucket_entities(Index::Score(*player)).len();
+            let count = board.seeds(Index::Score(*player));
             **text = count.to_string();
         });
 
@@ -93,38 +102,65 @@ fn update_labels(
 }
 
 #[derive(Debug, Clone, Copy, Resource, Deref)]
-pub struct WinnerFound(SystemId<In<Player>>);
+pub struct WinnerFound(SystemId<In<Outcome>>);
 
 impl SystemInResource for WinnerFound {
-    type Input = In<Player>;
+    type Input = In<Outcome>;
 
     fn system_id(&self) -> SystemId<Self::Input> {
         self.0
     }
 }
 
-pub type DeclareWinner = RunSystem<WinnerFound, Player, In<Player>>;
+pub type DeclareWinner = RunSystem<WinnerFound, Outcome, In<Outcome>>;
 
 pub fn winner_found(
-    In(winner): In<Player>,
+    In(outcome): In<Outcome>,
     mut lights: Query<&mut PointLight>,
     agent: Single<Entity, With<SequentialActions>>,
     mut commands: Commands,
     game_assets: Res<GameAssets>,
+    mut prefs: ResMut<Preferences>,
+    chosen: Res<ChosenVariant>,
+    board: Res<Board>,
+    players: Res<Players>,
+    started: Res<GameStarted>,
+    mut game_over: EventWriter<GameOver>,
 ) {
-    let Player(winner) = winner;
+    game_over.send(GameOver { outcome });
+    clear_saved_game(&mut prefs, &mut commands);
+    // Enough to reproduce the game in a bug report.
+    tracing::info!(
+        "Game over at {} after {}",
+        board.position(),
+        format_moves(&board.moves())
+    );
     for mut light in lights.iter_mut() {
         light.intensity = 0.0;
     }
     commands.actions(*agent).start(false).add(SleepPieces);
-    spawn_win_text(winner, &mut commands, &game_assets);
+    let record = GameRecord::new(&chosen, &board, &players, **started);
+    spawn_win_text(outcome, record, &mut commands, &game_assets);
 }
 
-fn spawn_win_text(winner: usize, commands: &mut Commands, game_assets: &Res<GameAssets>) {
-    assert!(winner < 2, "Invalid winner index");
-
-    const WINNER_NAMES: [&str; 2] = ["Blue", "Green"];
-    const COLORS: [Color; 2] = [Color::Srgba(LIGHT_CYAN), Color::Srgba(GREEN)];
+fn spawn_win_text(
+    outcome: Outcome,
+    record: GameRecord,
+    commands: &mut Commands,
+    game_assets: &Res<GameAssets>,
+) {
+    const COLORS: [Srgba; MAX_PLAYER_COUNT] = [LIGHT_CYAN, GREEN, CRIMSON, ORANGE];
+
+    let (message, color) = match outcome {
+        Outcome::Winner(Player(winner)) => {
+            assert!(winner < MAX_PLAYER_COUNT, "Invalid winner index");
+            (
+                format!("{} Player Wins!", PLAYER_NAMES[winner]),
+                Color::Srgba(COLORS[winner]),
+            )
+        }
+        Outcome::Draw => ("Draw!".to_string(), Color::WHITE),
+    };
     commands
         .spawn((
             WinnerButton,
@@ -150,9 +186,9 @@ fn spawn_win_text(winner: usize, commands: &mut Commands, game_assets: &Res<Game
         .with_children(|parent| {
             parent.spawn((
                 WinnerText,
-                Text::new(format!("{} Player Wins!", WINNER_NAMES[winner])),
+                Text::new(message),
                 TextFont::from_font(game_assets.main_font.clone()).with_font_size(50.0),
-                TextColor(COLORS[winner]),
+                TextColor(color),
                 TextLayout::new_with_justify(JustifyText::Center),
                 FocusPolicy::Pass,
                 Node {
@@ -161,5 +197,53 @@ fn spawn_win_text(winner: usize, commands: &mut Commands, game_assets: &Res<Game
                     ..Default::default()
                 },
             ));
+            for format in [Format::Json, Format::MessagePack] {
+                spawn_export_button(parent, &game_assets.main_font, record.clone(), format);
+            }
+        });
+}
+
+/// Spawns a button that writes the finished game out as a record in the given `format`.
+fn spawn_export_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    record: GameRecord,
+    format: Format,
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                align_self: AlignSelf::Center,
+                ..Default::default()
+            },
+            BackgroundColor(Color::NONE),
+        ))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::WHITE))
+        .observe(
+            move |mut click: Trigger<Pointer<Click>>,
+                  children: Query<&Children>,
+                  mut texts: Query<&mut Text>| {
+                // Exporting shouldn't also leave for the menu.
+                click.propagate(false);
+                let message = match record.export(format) {
+                    Ok(path) => format!("Saved to {}", path.display()),
+                    Err(e) => {
+                        tracing::warn!("{e}");
+                        format!("Unable to save {}", format.name())
+                    }
+                };
+                let children = children.get(click.entity()).unwrap();
+                let mut text = texts.get_mut(children[0]).unwrap();
+                **text = message;
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(format!("Export {}", format.name())),
+                TextFont::from_font(font.clone()).with_font_size(25.0),
+                TextColor(Color::WHITE),
+            ));
         });
 }
@@ -0,0 +1,242 @@
+use bevy::{
+    app,
+    color::palettes::css::{GOLD, SLATE_GRAY},
+    ecs::system::SystemId,
+    prelude::*,
+};
+use bevy_sequential_actions::{ActionsProxy, ModifyActions, SequentialActions};
+use serde::{Deserialize, Serialize};
+use stones_rules::variants::Index;
+
+use crate::{
+    events::MoveTakenBack,
+    game::{
+        ai::{PlayerKind, Players},
+        Board, GameState, PlayerTurn, Selected,
+    },
+    ui::{hover_button, unhover_button},
+};
+
+use super::{
+    turn::{animate_undo, BoardAtRest, NextPlayer, PlayerMove, Settle},
+    ui::UpdateLabels,
+    RunSystem, SystemInResource,
+};
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        let undo_move = app.register_system(undo_move);
+        app.insert_resource(UndoMoveResource(undo_move));
+        let redo_move = app.register_system(redo_move);
+        app.insert_resource(RedoMoveResource(redo_move));
+
+        app.insert_resource(Undo::default())
+            .insert_resource(UndoneMoves::default())
+            .add_systems(OnEnter(GameState::Playing), forget_undone_moves)
+            .add_systems(Update, undo_shortcuts.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Whether moves may be taken back in the current game.
+#[derive(Debug, Default, Clone, Copy, Resource, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Undo {
+    /// Every move stands, as in rated games and games against the computer.
+    #[default]
+    Off,
+    /// Any number of moves may be taken back and played again, for casual games.
+    Unlimited,
+}
+
+impl Undo {
+    /// The rule for a game between `players` unless another is chosen: unlimited when everyone is
+    /// human, and off when the computer plays.
+    pub fn for_players(players: &Players) -> Self {
+        if players.iter().all(|&p| p == PlayerKind::Human) {
+            Undo::Unlimited
+        } else {
+            Undo::Off
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Undo::Off => Undo::Unlimited,
+            Undo::Unlimited => Undo::Off,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Undo::Off => "Off",
+            Undo::Unlimited => "Unlimited",
+        }
+    }
+}
+
+/// The moves that were taken back, most recent last, ready to be played again.
+#[derive(Debug, Default, Clone, Resource, Deref, DerefMut)]
+pub struct UndoneMoves(Vec<Index>);
+
+#[derive(Clone, Copy, Debug, Resource, Deref)]
+pub struct UndoMoveResource(SystemId);
+
+impl SystemInResource for UndoMoveResource {
+    type Input = ();
+
+    fn system_id(&self) -> SystemId {
+        self.0
+    }
+}
+
+pub type UndoMove = RunSystem<UndoMoveResource>;
+
+#[derive(Clone, Copy, Debug, Resource, Deref)]
+pub struct RedoMoveResource(SystemId);
+
+impl SystemInResource for RedoMoveResource {
+    type Input = ();
+
+    fn system_id(&self) -> SystemId {
+        self.0
+    }
+}
+
+pub type RedoMove = RunSystem<RedoMoveResource>;
+
+fn forget_undone_moves(mut undone: ResMut<UndoneMoves>) {
+    undone.clear();
+}
+
+/// Takes back the last move, sending the stones back to the buckets they came from.
+fn undo_move(
+    undo: Res<Undo>,
+    mut board: ResMut<Board>,
+    mut p_turn: ResMut<PlayerTurn>,
+    mut undone: ResMut<UndoneMoves>,
+    mut at_rest: ResMut<BoardAtRest>,
+    mut selected: ResMut<Selected>,
+    mut lights: Query<&mut PointLight>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut taken_back: EventWriter<MoveTakenBack>,
+    mut commands: Commands,
+) {
+    // Moves can only be taken back while waiting for somebody to pick one.
+    if *undo == Undo::Off || *p_turn == PlayerTurn::None {
+        return;
+    }
+    let Some(&index) = board.moves().last() else {
+        return;
+    };
+
+    board.undo_move();
+    taken_back.send(MoveTakenBack {
+        player: board.current_player(),
+        from: index,
+    });
+    // Playing the move again on a copy tells us where every stone went.
+    let events = board.clone_box().perform_move(index);
+    let mut actions = animate_undo(&mut board, &events);
+    actions.push(Box::new(Settle::default()));
+    actions.push(Box::new(NextPlayer(board.current_player())));
+    at_rest.disturb();
+
+    *p_turn = PlayerTurn::None;
+    **selected = None;
+    undone.push(index);
+    lights.par_iter_mut().for_each(|mut light| {
+        light.intensity = 0.0;
+    });
+    commands
+        .actions(*agent)
+        .start(false)
+        .add(UpdateLabels::new())
+        .add(actions);
+}
+
+/// Plays the last move that was taken back again.
+fn redo_move(
+    undo: Res<Undo>,
+    p_turn: Res<PlayerTurn>,
+    undone: Res<UndoneMoves>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    if *undo == Undo::Off || *p_turn == PlayerTurn::None {
+        return;
+    }
+    let Some(&index) = undone.last() else {
+        return;
+    };
+
+    commands.actions(*agent).add(PlayerMove::with_input(index));
+}
+
+/// Ctrl+Z takes back a move, and Ctrl+Y or Ctrl+Shift+Z plays it again.
+fn undo_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    let command = keys.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    if !command {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keys.just_pressed(KeyCode::KeyZ) && !shift {
+        commands.actions(*agent).add(UndoMove::new());
+    } else if keys.just_pressed(KeyCode::KeyY) || (keys.just_pressed(KeyCode::KeyZ) && shift) {
+        commands.actions(*agent).add(RedoMove::new());
+    }
+}
+
+/// Spawns the undo and redo buttons into the bar at the top of the screen.
+pub fn spawn_undo_buttons(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    spawn_button(parent, font, "Undo", |mut commands, agent| {
+        commands.actions(agent).add(UndoMove::new());
+    });
+    spawn_button(parent, font, "Redo", |mut commands, agent| {
+        commands.actions(agent).add(RedoMove::new());
+    });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    on_click: fn(Commands, Entity),
+) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(40.0),
+                ..Default::default()
+            },
+            BackgroundColor(Color::NONE),
+        ))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::Srgba(SLATE_GRAY)))
+        .observe(
+            move |_click: Trigger<Pointer<Click>>,
+                  agent: Single<Entity, With<SequentialActions>>,
+                  commands: Commands| {
+                on_click(commands, *agent);
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont::from_font(font.clone()).with_font_size(20.0),
+                TextColor(Color::Srgba(SLATE_GRAY)),
+            ));
+        });
+}
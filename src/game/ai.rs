@@ -0,0 +1,221 @@
+use std::time::Duration;
+
+use bevy::{
+    app,
+    prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+};
+use bevy_sequential_actions::{ActionsProxy, ModifyActions, SequentialActions};
+use serde::{Deserialize, Serialize};
+use stones_rules::{
+    ai::{Minimax, MonteCarlo, Search},
+    variants::Index,
+};
+
+use crate::{
+    game::{Board, GameState, Player},
+    MAX_PLAYER_COUNT,
+};
+
+use super::actions::turn::PlayerMove;
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Players::default())
+            .insert_resource(Computer::default())
+            .add_systems(
+                Update,
+                poll_computer_move.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Who picks the moves for a player.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlayerKind {
+    /// Moves are picked by clicking on the buckets.
+    #[default]
+    Human,
+    /// Moves are picked by searching ahead through the rules.
+    Computer(Computer),
+}
+
+/// The search the computer uses to pick its moves.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Engine {
+    /// Alpha-beta search, looking a fixed number of moves ahead.
+    #[default]
+    Minimax,
+    /// Monte Carlo tree search, which plays out random games instead of counting stores.
+    MonteCarlo,
+}
+
+impl Engine {
+    pub fn next(self) -> Self {
+        match self {
+            Engine::Minimax => Engine::MonteCarlo,
+            Engine::MonteCarlo => Engine::Minimax,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Engine::Minimax => "Minimax",
+            Engine::MonteCarlo => "Monte Carlo",
+        }
+    }
+}
+
+/// How hard the minimax search tries to find a good move.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn next(self) -> Self {
+        match self {
+            Difficulty::Easy => Difficulty::Medium,
+            Difficulty::Medium => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Easy,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// The search the computer uses at this difficulty.
+    pub fn search(self) -> Minimax {
+        let (depth, millis) = match self {
+            Difficulty::Easy => (2, 250),
+            Difficulty::Medium => (6, 1000),
+            Difficulty::Hard => (14, 3000),
+        };
+        Minimax {
+            depth,
+            time_budget: Duration::from_millis(millis),
+        }
+    }
+}
+
+/// How the computer opponent plays, as chosen from the menu.
+#[derive(Debug, Clone, Copy, Resource, PartialEq, Eq, Hash)]
+pub struct Computer {
+    pub engine: Engine,
+    /// Only used by [`Engine::Minimax`].
+    pub difficulty: Difficulty,
+    /// Only used by [`Engine::MonteCarlo`].
+    pub iterations: u32,
+    /// Only used by [`Engine::MonteCarlo`].
+    pub time_limit: Duration,
+}
+
+impl Default for Computer {
+    fn default() -> Self {
+        Self {
+            engine: Engine::default(),
+            difficulty: Difficulty::default(),
+            iterations: Self::ITERATIONS[1],
+            time_limit: Self::TIME_LIMITS[1],
+        }
+    }
+}
+
+impl Computer {
+    const ITERATIONS: [u32; 4] = [1_000, 10_000, 50_000, 200_000];
+    const TIME_LIMITS: [Duration; 4] = [
+        Duration::from_millis(500),
+        Duration::from_secs(1),
+        Duration::from_secs(2),
+        Duration::from_secs(5),
+    ];
+
+    pub fn next_iterations(&mut self) {
+        self.iterations = next_option(&Self::ITERATIONS, self.iterations);
+    }
+
+    pub fn next_time_limit(&mut self) {
+        self.time_limit = next_option(&Self::TIME_LIMITS, self.time_limit);
+    }
+
+    pub fn search(&self) -> Box<dyn Search> {
+        match self.engine {
+            Engine::Minimax => Box::new(self.difficulty.search()),
+            Engine::MonteCarlo => Box::new(MonteCarlo {
+                iterations: self.iterations,
+                time_limit: self.time_limit,
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// The option after `current`, wrapping around to the first.
+fn next_option<T: Copy + PartialEq>(options: &[T], current: T) -> T {
+    let next = options
+        .iter()
+        .position(|&option| option == current)
+        .map_or(0, |i| (i + 1) % options.len());
+    options[next]
+}
+
+/// Who is playing each side of the board, with any sides the variant doesn't use left over.
+#[derive(Debug, Default, Clone, Copy, Resource, Deref, DerefMut)]
+pub struct Players(pub [PlayerKind; MAX_PLAYER_COUNT]);
+
+impl Players {
+    pub fn is_human(&self, Player(player): Player) -> bool {
+        self.0[player] == PlayerKind::Human
+    }
+}
+
+/// A search for the computer's next move, running in the background.
+#[derive(Component)]
+struct ThinkingTask(Task<Option<Index>>);
+
+/// Starts searching for `player`'s move in the background, if the computer is playing them.
+pub fn start_thinking(world: &mut World, Player(player): Player) {
+    let PlayerKind::Computer(computer) = world.resource::<Players>()[player] else {
+        return;
+    };
+
+    let variant = world.resource::<Board>().clone_box();
+    let search = computer.search();
+    let task = AsyncComputeTaskPool::get().spawn(async move { search.best_move(&*variant) });
+    world.spawn((
+        Name::new("Computer Move"),
+        ThinkingTask(task),
+        StateScoped(GameState::Playing),
+    ));
+}
+
+fn poll_computer_move(
+    mut tasks: Query<(Entity, &mut ThinkingTask)>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    for (entity, mut task) in &mut tasks {
+        let Some(best) = block_on(poll_once(&mut task.0)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+
+        let Some(index) = best else {
+            tracing::warn!("The computer has no move to make");
+            continue;
+        };
+        commands.actions(*agent).add(PlayerMove::with_input(index));
+    }
+}
@@ -0,0 +1,128 @@
+//! Choosing a hole with the keyboard or a gamepad, for playing without a pointer.
+//!
+//! The arrow keys, d-pad or left stick move a cursor between the holes the current player may sow
+//! from, going whichever way looks right from where the camera is, and Enter or the south button
+//! sows from it.
+
+use bevy::{app, prelude::*};
+use bevy_sequential_actions::{ActionsProxy, ModifyActions, SequentialActions};
+use stones_rules::variants::Index;
+
+use crate::{
+    camera::OrbitCamera,
+    ui::focus::{MenuFocus, Navigation},
+};
+
+use super::{
+    actions::{board::HIGHLIGHT, turn::PlayerMove},
+    Board, GameState, Hole, MoveSelection, Player,
+};
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FocusedHole>()
+            .add_systems(OnExit(GameState::Playing), clear_hole_focus)
+            .add_systems(
+                Update,
+                (move_hole_focus, sow_focused_hole)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The hole the keyboard or gamepad cursor is on, lit up like a hole under the pointer.
+#[derive(Debug, Default, Clone, Copy, Resource, Deref)]
+pub struct FocusedHole(Option<Index>);
+
+fn clear_hole_focus(mut focused: ResMut<FocusedHole>) {
+    **focused = None;
+}
+
+/// Moves the cursor to the nearest hole the current player may sow from, the way it was pushed.
+///
+/// The first push picks the hole furthest back from that way, so pushing right starts from the
+/// left.
+fn move_hole_focus(
+    mut navigation: Navigation,
+    mut focused: ResMut<FocusedHole>,
+    selection: MoveSelection,
+    board: Res<Board>,
+    camera: Single<(&Camera, &GlobalTransform), With<OrbitCamera>>,
+    mut lights: Query<(&Player, &Hole, &mut PointLight)>,
+) {
+    let legal = selection.legal_moves();
+    // The move was made, or it's somebody else's turn, so the cursor has nothing to point at.
+    if focused.is_some_and(|index| !legal.contains(&index)) {
+        **focused = None;
+    }
+    let Some(direction) = navigation.direction() else {
+        return;
+    };
+
+    // Where each hole is on screen, with up as positive to match the direction.
+    let (camera, transform) = *camera;
+    let on_screen = |index: Index| {
+        camera
+            .world_to_viewport(transform, board.bucket_position(index))
+            .ok()
+            .map(|position| Vec2::new(position.x, -position.y))
+    };
+    let holes = legal
+        .into_iter()
+        .filter_map(|index| Some((index, on_screen(index)?)))
+        .collect::<Vec<_>>();
+
+    let next = match focused.and_then(on_screen) {
+        Some(from) => holes
+            .iter()
+            .map(|&(index, position)| (index, position - from))
+            .filter(|(_, offset)| offset.dot(direction) > f32::EPSILON)
+            // Holes off to the side of the way it was pushed count as further away.
+            .min_by(|(_, a), (_, b)| {
+                let cost = |offset: Vec2| offset.length() + 2.0 * offset.perp_dot(direction).abs();
+                cost(*a).total_cmp(&cost(*b))
+            })
+            .map(|(index, _)| index),
+        None => holes
+            .iter()
+            .min_by(|(_, a), (_, b)| a.dot(direction).total_cmp(&b.dot(direction)))
+            .map(|&(index, _)| index),
+    };
+    let Some(next) = next else {
+        return;
+    };
+
+    for (&player, &hole, mut light) in &mut lights {
+        let index = Index::Player(player, hole);
+        if Some(index) == **focused {
+            light.intensity = 0.0;
+        }
+        if index == next {
+            light.intensity = HIGHLIGHT;
+        }
+    }
+    **focused = Some(next);
+}
+
+/// Sows from the hole under the cursor once it's chosen, unless a button has the focus instead.
+fn sow_focused_hole(
+    navigation: Navigation,
+    menu: Res<MenuFocus>,
+    mut focused: ResMut<FocusedHole>,
+    selection: MoveSelection,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    if !navigation.confirmed() || menu.is_active() {
+        return;
+    }
+    let Some(index) = focused.filter(|&index| selection.is_legal(index)) else {
+        return;
+    };
+
+    commands.actions(*agent).add(PlayerMove::with_input(index));
+    **focused = None;
+}
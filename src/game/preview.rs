@@ -0,0 +1,197 @@
+//! Showing what a move would do before it's made: where the last seed lands, which holes it
+//! captures, and how much the mover's store gains.
+//!
+//! The move is played out on a copy of the board, so nothing about the game itself changes.
+
+use bevy::{
+    app,
+    color::palettes::css::{CRIMSON, GOLD},
+    prelude::*,
+};
+use bevy_mod_billboard::BillboardText;
+use bevy_prefs_lite::Preferences;
+use stones_rules::variants::{Index, MoveEvent};
+
+use crate::{
+    assets::GameAssets,
+    ui::settings::{load_settings, store_settings},
+};
+
+use super::{
+    actions::board::HIGHLIGHT, focus::FocusedHole, Board, GameState, InGame, MoveSelection,
+    Selected,
+};
+
+const GROUP: &str = "preview";
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PreviewSettings>()
+            .init_resource::<HoveredHole>()
+            .init_resource::<Previewed>()
+            .add_systems(Startup, load_preview_settings)
+            .add_systems(OnExit(InGame), forget_preview)
+            .add_systems(
+                Update,
+                (
+                    store_preview_settings.run_if(resource_changed::<PreviewSettings>),
+                    show_preview.run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
+
+/// Whether moves are previewed at all, so purists and rated games can do their own counting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Resource)]
+pub struct PreviewSettings {
+    pub enabled: bool,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+impl PreviewSettings {
+    pub fn toggle_enabled(&mut self) {
+        self.enabled = !self.enabled;
+    }
+}
+
+/// The hole under the pointer, if the current player may sow from it.
+#[derive(Debug, Default, Clone, Copy, Resource, Deref, DerefMut)]
+pub struct HoveredHole(Option<Index>);
+
+/// The move the preview is showing, and how far into the game, so it can tell when it's stale.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Resource)]
+struct Previewed(Option<(Index, usize)>);
+
+/// Part of the preview, cleared away when it changes.
+#[derive(Debug, Clone, Copy, Component)]
+struct PreviewMarker;
+
+fn forget_preview(mut previewed: ResMut<Previewed>, mut hovered: ResMut<HoveredHole>) {
+    *previewed = Previewed::default();
+    **hovered = None;
+}
+
+/// Previews the move from the hole being held, or else the one under the pointer or the cursor,
+/// replacing the last preview when that changes.
+fn show_preview(
+    settings: Res<PreviewSettings>,
+    selected: Res<Selected>,
+    hovered: Res<HoveredHole>,
+    focused: Res<FocusedHole>,
+    selection: MoveSelection,
+    board: Res<Board>,
+    game_assets: Res<GameAssets>,
+    mut previewed: ResMut<Previewed>,
+    markers: Query<Entity, With<PreviewMarker>>,
+    mut commands: Commands,
+) {
+    let index = [**selected, **hovered, **focused]
+        .into_iter()
+        .flatten()
+        .find(|&index| selection.is_legal(index))
+        .filter(|_| settings.enabled);
+    let wanted = Previewed(index.map(|index| (index, board.moves().len())));
+    if wanted == *previewed {
+        return;
+    }
+    *previewed = wanted;
+    for marker in &markers {
+        commands.entity(marker).despawn_recursive();
+    }
+    let Some(index) = index else {
+        return;
+    };
+
+    let mover = board.current_player();
+    let mut after = board.clone_box();
+    let events = after.perform_move(index);
+    let landing = events.iter().rev().find_map(|event| match *event {
+        MoveEvent::Sow { to } => Some(to),
+        _ => None,
+    });
+    // Seeds swept up once the game is over weren't won by the move, so only captures light up.
+    let captured = events
+        .iter()
+        .filter_map(|event| match *event {
+            MoveEvent::Capture { from, .. } => Some(from),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    // A hole that is captured from where the last seed lands only needs the one light.
+    let landing = landing.filter(|to| !captured.contains(to));
+    for (bucket, color) in landing
+        .map(|to| (to, Color::WHITE))
+        .into_iter()
+        .chain(captured.iter().map(|&from| (from, Color::Srgba(CRIMSON))))
+    {
+        let mut position = board.bucket_position(bucket);
+        position.y = 0.01;
+        commands.spawn((
+            Name::from(format!("preview_{bucket}")),
+            PreviewMarker,
+            PointLight {
+                color,
+                intensity: HIGHLIGHT,
+                range: 0.5,
+                radius: 0.5,
+                ..Default::default()
+            },
+            Transform::from_translation(position),
+            StateScoped(InGame),
+        ));
+    }
+
+    let store = Index::Score(mover);
+    let gain = events
+        .iter()
+        .map(|event| match *event {
+            MoveEvent::Sow { to } if to == store => 1,
+            MoveEvent::Capture { to, count, .. } if to == store => count,
+            _ => 0,
+        })
+        .sum::<usize>();
+    let position = board.label_position(store) + Vec3::Y * 0.03;
+    commands.spawn((
+        Name::from("preview_gain"),
+        PreviewMarker,
+        BillboardText::new(format!("+{gain}")),
+        TextLayout::new_with_justify(JustifyText::Center),
+        TextFont::from_font(game_assets.main_font.clone()).with_font_size(30.0),
+        TextColor(Color::Srgba(GOLD)),
+        Transform::from_translation(position).with_scale(Vec3::splat(0.001)),
+        StateScoped(InGame),
+    ));
+}
+
+/// Picks up whether to preview moves from last time.
+fn load_preview_settings(prefs: Res<Preferences>, mut settings: ResMut<PreviewSettings>) {
+    load_settings(&prefs, GROUP, |group| {
+        if let Some(enabled) = group.get::<bool>("enabled") {
+            settings.enabled = enabled;
+        }
+    });
+}
+
+/// Remembers any change to the preview settings for next time.
+fn store_preview_settings(
+    settings: Res<PreviewSettings>,
+    mut prefs: ResMut<Preferences>,
+    mut commands: Commands,
+) {
+    // The settings were only just loaded, so there is nothing new to remember.
+    if settings.is_added() {
+        return;
+    }
+
+    let what = "the preview settings";
+    store_settings(&mut prefs, &mut commands, GROUP, what, |group| {
+        group.set("enabled", settings.enabled);
+    });
+}
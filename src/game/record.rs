@@ -0,0 +1,393 @@
+//! Records of played games, for archiving club games and sharing interesting positions.
+//!
+//! Records can be written as JSON, to be read and passed around by people, or as MessagePack,
+//! to be as small as possible.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{app, prelude::*};
+use serde::{Deserialize, Serialize};
+use stones_rules::{
+    notation::{parse_moves, ParseError},
+    variants::{
+        custom::{Custom, Definition},
+        kalah::{self, Kalah, Sweep},
+        oware::{GrandSlam, Oware},
+        ChosenVariant, Index, Outcome, Variant,
+    },
+    Position, PLAYER_COUNT,
+};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::{
+    ai::{Computer, PlayerKind, Players},
+    save::ResumeGame,
+    Board, GameState, Player,
+};
+
+/// Where a position to start a new game from is read from, in the records directory.
+const POSITION_FILE: &str = "position.txt";
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameStarted(now()))
+            .add_systems(OnEnter(GameState::Playing), start_clock);
+    }
+}
+
+/// When the game being played was started, in seconds since the Unix epoch.
+#[derive(Debug, Clone, Copy, Resource, Deref)]
+pub struct GameStarted(pub u64);
+
+fn start_clock(resume: Option<Res<ResumeGame>>, mut started: ResMut<GameStarted>) {
+    // A resumed game keeps the time it was first started.
+    started.0 = resume.map_or_else(now, |resume| resume.started_at);
+}
+
+/// The current time, in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// The rules a game was played with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum VariantRecord {
+    Kalah {
+        holes: usize,
+        seeds: usize,
+        sweep: Sweep,
+        #[serde(default = "nearest_camera")]
+        first: Player,
+    },
+    Oware {
+        grand_slam: GrandSlam,
+        #[serde(default = "nearest_camera")]
+        first: Player,
+    },
+    /// The whole definition is kept, so the game can be played through again even if the
+    /// variant file has since changed or been removed.
+    Custom {
+        definition: Definition,
+        first: Player,
+    },
+}
+
+/// Who moved first in records from before the opening player could be chosen.
+fn nearest_camera() -> Player {
+    Player(1)
+}
+
+impl VariantRecord {
+    pub fn new(chosen: &ChosenVariant) -> Self {
+        match chosen {
+            ChosenVariant::Kalah(game) => VariantRecord::Kalah {
+                holes: game.hole_count(),
+                seeds: game.starting_pieces(),
+                sweep: game.sweep(),
+                first: game.first_player(),
+            },
+            ChosenVariant::Oware(game) => VariantRecord::Oware {
+                grand_slam: game.grand_slam(),
+                first: game.first_player(),
+            },
+            ChosenVariant::Custom(game) => VariantRecord::Custom {
+                definition: game.definition().clone(),
+                first: game.first_player(),
+            },
+        }
+    }
+
+    /// The variant ready to play, or `None` if the parameters are out of range.
+    pub fn to_chosen(self) -> Option<ChosenVariant> {
+        match self {
+            VariantRecord::Kalah {
+                holes,
+                seeds,
+                sweep,
+                first,
+            } => {
+                let valid = kalah::HOLE_COUNTS.contains(&holes)
+                    && kalah::SEED_COUNTS.contains(&seeds)
+                    && *first < PLAYER_COUNT;
+                valid.then(|| {
+                    ChosenVariant::Kalah(Kalah::new(holes, seeds, sweep).with_first_player(first))
+                })
+            }
+            VariantRecord::Oware { grand_slam, first } => (*first < PLAYER_COUNT).then(|| {
+                ChosenVariant::Oware(Oware::with_grand_slam(grand_slam).with_first_player(first))
+            }),
+            VariantRecord::Custom { definition, first } => {
+                let game = Custom::new(definition).ok()?;
+                (*first < game.player_count())
+                    .then(|| ChosenVariant::Custom(game.with_first_player(first)))
+            }
+        }
+    }
+}
+
+/// Who played one side of the board.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlayerRecord {
+    Human,
+    Computer { engine: String },
+}
+
+/// Everything needed to play a game through again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub variant: VariantRecord,
+    /// The position the game was started from, in the game's notation, if it wasn't the opening
+    /// one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<String>,
+    /// Indexed by player.
+    pub players: Vec<PlayerRecord>,
+    /// Every move, in the order they were played.
+    pub moves: Vec<Index>,
+    /// How the game turned out, or `None` if it was never finished.
+    pub result: Option<Outcome>,
+    /// In seconds since the Unix epoch.
+    pub started_at: u64,
+    /// In seconds since the Unix epoch, if the game was finished.
+    pub finished_at: Option<u64>,
+}
+
+impl GameRecord {
+    pub fn new(chosen: &ChosenVariant, board: &Board, players: &Players, started_at: u64) -> Self {
+        let result = board.outcome();
+        let variant = VariantRecord::new(chosen);
+        let opening = variant.clone().to_chosen();
+        let position = chosen.position();
+        Self {
+            position: opening
+                .is_none_or(|opening| opening.position() != position)
+                .then(|| position.to_string()),
+            variant,
+            players: players
+                .iter()
+                .take(board.player_count())
+                .map(|kind| match kind {
+                    PlayerKind::Human => PlayerRecord::Human,
+                    PlayerKind::Computer(computer) => PlayerRecord::Computer {
+                        engine: computer.engine.name().to_string(),
+                    },
+                })
+                .collect(),
+            moves: board.moves(),
+            result,
+            started_at,
+            finished_at: result.map(|_| now()),
+        }
+    }
+
+    /// Plays every recorded move, returning the variant the game was played with and the game
+    /// as it stood at the end, or `None` if a move couldn't have been played.
+    pub fn replay(&self) -> Option<(ChosenVariant, Box<dyn Variant>)> {
+        let mut chosen = self.variant.clone().to_chosen()?;
+        if let Some(position) = &self.position {
+            let position = position
+                .parse::<Position>()
+                .inspect_err(|e| tracing::warn!("{e}"))
+                .ok()?;
+            if !chosen.set_position(position) {
+                return None;
+            }
+        }
+        let variant = play(&chosen, &self.moves)?;
+        Some((chosen, variant))
+    }
+
+    /// Who played each side, with the computer using its current settings.
+    pub fn players(&self, computer: Computer) -> Players {
+        Players(core::array::from_fn(|p| match self.players.get(p) {
+            Some(PlayerRecord::Computer { .. }) => PlayerKind::Computer(computer),
+            _ => PlayerKind::Human,
+        }))
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_msgpack(&self) -> Result<Vec<u8>, Error> {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
+
+    pub fn from_msgpack(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+
+    /// Writes the record into the records directory, returning where it was written.
+    pub fn export(&self, format: Format) -> Result<PathBuf, Error> {
+        let dir = records_dir();
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.{}", self.started_at, format.extension()));
+        match format {
+            Format::Json => fs::write(&path, self.to_json()?)?,
+            Format::MessagePack => fs::write(&path, self.to_msgpack()?)?,
+        }
+        Ok(path)
+    }
+
+    /// Reads a record, in whichever format its extension says it's in.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        match Format::from_path(path) {
+            Some(Format::Json) => Self::from_json(&fs::read_to_string(path)?),
+            Some(Format::MessagePack) => Self::from_msgpack(&fs::read(path)?),
+            None => Err(Error::UnknownFormat(path.to_path_buf())),
+        }
+    }
+
+    /// Every record in the records directory, the most recently started game first.
+    pub fn list() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(records_dir()) else {
+            return Vec::new();
+        };
+        let mut paths = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| Format::from_path(path).is_some())
+            .collect::<Vec<_>>();
+        // Records are named after when they were started, and copied in ones may be named anything.
+        paths.sort_by_cached_key(|path| {
+            core::cmp::Reverse(
+                path.file_stem()
+                    .and_then(|stem| stem.to_str()?.parse::<u64>().ok()),
+            )
+        });
+        paths
+    }
+}
+
+/// Sets up a game of `chosen` and plays `moves` in it, or returns `None` if a move couldn't have
+/// been played.
+fn play(chosen: &ChosenVariant, moves: &[Index]) -> Option<Box<dyn Variant>> {
+    let mut variant = chosen.to_variant();
+    for &index in moves {
+        if !variant.is_legal_move(index, variant.current_player()) {
+            return None;
+        }
+        variant.perform_move(index);
+    }
+    Some(variant)
+}
+
+/// A position to start a new game from, read from [`POSITION_FILE`] in the records directory.
+///
+/// The file holds a position in the game's notation on its first line, followed by any moves
+/// played from it, so puzzles and positions from other games can be set up and played on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedPosition {
+    pub position: Position,
+    /// The moves played from the position before the game is handed over.
+    pub moves: Vec<Index>,
+}
+
+impl SharedPosition {
+    /// The position waiting to be played, if there is one that can be read.
+    pub fn load() -> Option<Self> {
+        let text = fs::read_to_string(records_dir().join(POSITION_FILE)).ok()?;
+        Self::parse(&text)
+            .inspect_err(|e| tracing::warn!("{e}"))
+            .ok()
+    }
+
+    fn parse(text: &str) -> Result<Self, Error> {
+        let text = text.trim();
+        let (position, moves) = text.split_once('\n').unwrap_or((text, ""));
+        Ok(Self {
+            position: position.trim().parse()?,
+            moves: parse_moves(moves)?,
+        })
+    }
+
+    /// Starts `chosen` from the position and plays the moves, returning the game as it then
+    /// stands, or `None` if the position doesn't fit its board or a move couldn't be played.
+    pub fn set_up(&self, chosen: &mut ChosenVariant) -> Option<Box<dyn Variant>> {
+        if !chosen.set_position(self.position) {
+            return None;
+        }
+        play(chosen, &self.moves)
+    }
+}
+
+/// How a record is written to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    Json,
+    MessagePack,
+}
+
+impl Format {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::MessagePack => "msgpack",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Json => "JSON",
+            Format::MessagePack => "MessagePack",
+        }
+    }
+
+    fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Format::Json),
+            "msgpack" => Some(Format::MessagePack),
+            _ => None,
+        }
+    }
+}
+
+/// Where game records are kept, alongside the preferences in the platform's data directory.
+///
+/// Records copied in from elsewhere are listed for review along with the ones the game wrote.
+pub fn records_dir() -> PathBuf {
+    #[cfg(target_os = "android")]
+    if let Some(path) = bevy::window::ANDROID_APP
+        .get()
+        .and_then(|app| app.internal_data_path())
+    {
+        return path.join("games");
+    }
+    directories::BaseDirs::new()
+        .map(|dirs| dirs.preference_dir().join(crate::APP_NAME))
+        .unwrap_or_default()
+        .join("games")
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Failed to access game record: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to read or write JSON game record: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Failed to write MessagePack game record: {0}")]
+    MessagePackEncode(#[from] rmp_serde::encode::Error),
+
+    #[error("Failed to read MessagePack game record: {0}")]
+    MessagePackDecode(#[from] rmp_serde::decode::Error),
+
+    #[error("Failed to read position: {0}")]
+    Notation(#[from] ParseError),
+
+    #[error("Not a game record: {0:?}")]
+    UnknownFormat(PathBuf),
+}
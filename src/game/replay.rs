@@ -0,0 +1,522 @@
+//! Watching a recorded game back, one move at a time or at a steady pace, without being able to
+//! change it.
+
+use bevy::{
+    app,
+    color::palettes::css::{GOLD, SLATE_GRAY},
+    ecs::system::SystemId,
+    prelude::*,
+};
+use bevy_sequential_actions::{Action, ActionsProxy, ModifyActions, SequentialActions, StopReason};
+use stones_rules::variants::{ChosenVariant, Index};
+
+use crate::{
+    assets::GameAssets,
+    game::{Board, GameState, PlayerTurn, Stone},
+    ui::{focus::MenuFocus, hover_button, unhover_button},
+};
+
+use super::actions::{
+    board::{SpawnBoardAndPieces, SpawnPieces},
+    turn::{animate_move, animate_undo, BoardAtRest, Settle, Wait},
+    ui::UpdateLabels,
+    RunSystem, SystemInResource,
+};
+
+/// How many times faster than normal the moves may be played, slowest first.
+const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+
+/// The pause between moves when playing at normal speed, in seconds.
+const PAUSE: f32 = 1.0;
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        let step_replay = app.register_system(step_replay);
+        app.insert_resource(StepReplayResource(step_replay));
+        let seek_replay = app.register_system(seek_replay);
+        app.insert_resource(SeekReplayResource(seek_replay));
+
+        app.insert_resource(Replay::default())
+            .add_systems(OnEnter(GameState::Replay), (setup_replay, spawn_replay_bar))
+            .add_systems(OnExit(GameState::Replay), stop_replay)
+            .add_systems(
+                Update,
+                (
+                    replay_shortcuts,
+                    update_replay_bar.run_if(resource_changed::<Replay>),
+                )
+                    .run_if(in_state(GameState::Replay)),
+            );
+    }
+}
+
+/// The game being replayed, and how far through it we are.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct Replay {
+    /// Every move in the game, in the order they were played.
+    moves: Vec<Index>,
+    /// How many of the moves are on the board.
+    shown: usize,
+    /// Whether the moves are being played one after another.
+    playing: bool,
+    /// Counts how many times autoplay was started, so a stale [`Autoplay`] can tell it was
+    /// replaced.
+    run: usize,
+    /// Index into [`SPEEDS`].
+    speed: usize,
+}
+
+impl Replay {
+    /// A replay of `moves`, starting from the beginning at normal speed.
+    pub fn new(moves: Vec<Index>) -> Self {
+        Self {
+            moves,
+            speed: 1,
+            ..Default::default()
+        }
+    }
+
+    fn at_end(&self) -> bool {
+        self.shown >= self.moves.len()
+    }
+
+    /// How long to wait once a move has finished before playing the next one.
+    fn pause(&self) -> f32 {
+        PAUSE / SPEEDS[self.speed]
+    }
+}
+
+/// Which way to step through the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    Forward,
+    Back,
+}
+
+#[derive(Clone, Copy, Debug, Resource, Deref)]
+pub struct StepReplayResource(SystemId<In<Step>>);
+
+impl SystemInResource for StepReplayResource {
+    type Input = In<Step>;
+
+    fn system_id(&self) -> SystemId<Self::Input> {
+        self.0
+    }
+}
+
+pub type StepReplay = RunSystem<StepReplayResource, Step, In<Step>>;
+
+/// Jumps straight to the position after the given number of moves.
+#[derive(Clone, Copy, Debug, Resource, Deref)]
+pub struct SeekReplayResource(SystemId<In<usize>>);
+
+fn setup_replay(
+    mut board: ResMut<Board>,
+    chosen: Res<ChosenVariant>,
+    mut replay: ResMut<Replay>,
+    mut p_turn: ResMut<PlayerTurn>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    *board = Board::new(chosen.to_variant());
+    *p_turn = PlayerTurn::None;
+    replay.shown = 0;
+    replay.playing = false;
+    commands
+        .actions(*agent)
+        .add((SpawnBoardAndPieces, UpdateLabels::new()));
+}
+
+/// Drops any moves still being animated, so nothing touches the stones once they're gone.
+fn stop_replay(
+    mut replay: ResMut<Replay>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    replay.playing = false;
+    commands.actions(*agent).clear();
+}
+
+/// Plays the next move, or takes back the last one, moving the stones to match.
+fn step_replay(
+    In(step): In<Step>,
+    mut replay: ResMut<Replay>,
+    mut board: ResMut<Board>,
+    mut at_rest: ResMut<BoardAtRest>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    let mut actions = match step {
+        Step::Forward => {
+            let Some(&index) = replay.moves.get(replay.shown) else {
+                return;
+            };
+            replay.shown += 1;
+            let events = board.perform_move(index);
+            animate_move(&mut board, &events)
+        }
+        Step::Back => {
+            let Some(&index) = board.moves().last() else {
+                return;
+            };
+            replay.shown -= 1;
+            board.undo_move();
+            // Playing the move again on a copy tells us where every stone went.
+            let events = board.clone_box().perform_move(index);
+            animate_undo(&mut board, &events)
+        }
+    };
+    actions.push(Box::new(Settle::default()));
+    at_rest.disturb();
+    commands
+        .actions(*agent)
+        .start(false)
+        .add(UpdateLabels::new())
+        .add(actions);
+}
+
+/// Sets the board up as it was after `shown` moves, putting fresh stones in every bucket instead
+/// of animating each move in between.
+fn seek_replay(
+    In(shown): In<usize>,
+    mut replay: ResMut<Replay>,
+    mut board: ResMut<Board>,
+    chosen: Res<ChosenVariant>,
+    stones: Query<Entity, With<Stone>>,
+    mut at_rest: ResMut<BoardAtRest>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    let shown = shown.min(replay.moves.len());
+    if shown == replay.shown {
+        return;
+    }
+
+    let mut variant = chosen.to_variant();
+    for &index in &replay.moves[..shown] {
+        variant.perform_move(index);
+    }
+    *board = Board::new(variant);
+    replay.shown = shown;
+    for stone in &stones {
+        commands.entity(stone).despawn_recursive();
+    }
+
+    // Anything still queued would move stones that are gone, including the settle that would have
+    // ended a move cut short, so the fresh stones settle in its place before anything steps on.
+    commands.actions(*agent).clear();
+    at_rest.disturb();
+    commands
+        .actions(*agent)
+        .add((SpawnPieces, UpdateLabels::new(), Settle::default()));
+    if replay.playing {
+        commands.actions(*agent).add(Autoplay(replay.run));
+    }
+}
+
+/// Starts playing the moves one after another, or stops if they already are.
+fn toggle_autoplay(
+    replay: &mut Replay,
+    seek: SystemId<In<usize>>,
+    agent: Entity,
+    commands: &mut Commands,
+) {
+    replay.playing = !replay.playing;
+    if !replay.playing {
+        return;
+    }
+
+    replay.run += 1;
+    if replay.at_end() && replay.shown > 0 {
+        // Seeking picks autoplay up again once the board is reset.
+        commands.run_system_with_input(seek, 0);
+    } else {
+        commands.actions(agent).add(Autoplay(replay.run));
+    }
+}
+
+/// Plays the next move, then waits for it to finish before queueing the one after.
+struct Autoplay(usize);
+
+impl Action for Autoplay {
+    fn is_finished(&self, _agent: Entity, _world: &World) -> bool {
+        true
+    }
+
+    fn on_start(&mut self, agent: Entity, world: &mut World) -> bool {
+        let replay = world.resource::<Replay>();
+        if !replay.playing || replay.run != self.0 {
+            return true;
+        }
+        let pause = replay.pause();
+        if replay.at_end() {
+            world.resource_mut::<Replay>().playing = false;
+            return true;
+        }
+
+        world
+            .run_system_with_input(
+                world.resource::<StepReplayResource>().system_id(),
+                Step::Forward,
+            )
+            .unwrap();
+        // The step has queued its animation, waiting for the stones to land and settle, so this
+        // only has to pause before the next one.
+        world
+            .actions(agent)
+            .start(false)
+            .add((Wait::from_secs(pause), Autoplay(self.0)));
+
+        true
+    }
+
+    fn on_stop(&mut self, _agent: Option<Entity>, _world: &mut World, _reason: StopReason) {}
+}
+
+/// The arrow keys step through the moves, Home and End jump to either end of the game, Space
+/// starts or stops autoplay, and Up and Down change its speed.
+///
+/// Space is left to press the focused button instead, while a button has the focus.
+fn replay_shortcuts(
+    keys: Res<ButtonInput<KeyCode>>,
+    menu: Res<MenuFocus>,
+    mut replay: ResMut<Replay>,
+    seek: Res<SeekReplayResource>,
+    agent: Single<Entity, With<SequentialActions>>,
+    mut commands: Commands,
+) {
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        commands
+            .actions(*agent)
+            .add(StepReplay::with_input(Step::Forward));
+    } else if keys.just_pressed(KeyCode::ArrowLeft) {
+        commands
+            .actions(*agent)
+            .add(StepReplay::with_input(Step::Back));
+    } else if keys.just_pressed(KeyCode::Home) {
+        commands.run_system_with_input(**seek, 0);
+    } else if keys.just_pressed(KeyCode::End) {
+        commands.run_system_with_input(**seek, replay.moves.len());
+    } else if keys.just_pressed(KeyCode::Space) && !menu.is_active() {
+        toggle_autoplay(&mut replay, **seek, *agent, &mut commands);
+    } else if keys.just_pressed(KeyCode::ArrowUp) {
+        replay.speed = (replay.speed + 1).min(SPEEDS.len() - 1);
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        replay.speed = replay.speed.saturating_sub(1);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
+struct MoveNumberText;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct NotationText;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct PlayButton;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct SpeedButton;
+
+/// One notch of the scrub bar, standing for the position after this many moves.
+#[derive(Debug, Clone, Copy, Component)]
+struct ScrubNotch(usize);
+
+fn spawn_replay_bar(mut commands: Commands, game_assets: Res<GameAssets>, replay: Res<Replay>) {
+    let font = &game_assets.main_font;
+    commands
+        .spawn((
+            Node {
+                display: Display::Flex,
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            StateScoped(GameState::Replay),
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(Node {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Start,
+                    justify_content: JustifyContent::SpaceAround,
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        MoveNumberText,
+                        Text::new(move_number_label(&replay)),
+                        TextFont::from_font(font.clone()).with_font_size(40.0),
+                        TextColor(Color::WHITE),
+                    ));
+                    parent.spawn((
+                        NotationText,
+                        Text::new(notation_label(&replay)),
+                        TextFont::from_font(font.clone()).with_font_size(40.0),
+                        TextColor(Color::Srgba(GOLD)),
+                    ));
+                    spawn_button(parent, font, "|<", (), |seek, _, _, mut commands| {
+                        commands.run_system_with_input(seek, 0);
+                    });
+                    spawn_button(parent, font, "<", (), |_, agent, _, mut commands| {
+                        commands
+                            .actions(agent)
+                            .add(StepReplay::with_input(Step::Back));
+                    });
+                    spawn_button(
+                        parent,
+                        font,
+                        play_label(&replay),
+                        PlayButton,
+                        |seek, agent, replay, mut commands| {
+                            toggle_autoplay(replay, seek, agent, &mut commands);
+                        },
+                    );
+                    spawn_button(parent, font, ">", (), |_, agent, _, mut commands| {
+                        commands
+                            .actions(agent)
+                            .add(StepReplay::with_input(Step::Forward));
+                    });
+                    spawn_button(parent, font, ">|", (), |seek, _, replay, mut commands| {
+                        commands.run_system_with_input(seek, replay.moves.len());
+                    });
+                    spawn_button(
+                        parent,
+                        font,
+                        speed_label(&replay),
+                        SpeedButton,
+                        |_, _, replay, _| {
+                            replay.speed = (replay.speed + 1) % SPEEDS.len();
+                        },
+                    );
+                    spawn_button(parent, font, "Main Menu", (), |_, _, _, mut commands| {
+                        commands.set_state(GameState::Menu);
+                    });
+                });
+            parent
+                .spawn(Node {
+                    width: Val::Percent(80.0),
+                    height: Val::Px(12.0),
+                    flex_direction: FlexDirection::Row,
+                    column_gap: Val::Px(1.0),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    for shown in 1..=replay.moves.len() {
+                        spawn_scrub_notch(parent, shown);
+                    }
+                });
+        });
+}
+
+/// Spawns a button into the replay bar, which calls `on_click` with the seek system, the agent,
+/// the replay and commands.
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: impl Into<String>,
+    marker: impl Bundle,
+    on_click: fn(SystemId<In<usize>>, Entity, &mut Replay, Commands),
+) {
+    parent
+        .spawn((marker, Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::Srgba(SLATE_GRAY)))
+        .observe(
+            move |_click: Trigger<Pointer<Click>>,
+                  seek: Res<SeekReplayResource>,
+                  agent: Single<Entity, With<SequentialActions>>,
+                  mut replay: ResMut<Replay>,
+                  commands: Commands| {
+                on_click(**seek, *agent, &mut replay, commands);
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont::from_font(font.clone()).with_font_size(30.0),
+                TextColor(Color::Srgba(SLATE_GRAY)),
+            ));
+        });
+}
+
+/// Spawns a notch that jumps to the position after `shown` moves when it is clicked, or dragged
+/// over.
+fn spawn_scrub_notch(parent: &mut ChildBuilder, shown: usize) {
+    let seek = move |seek: Res<SeekReplayResource>, mut commands: Commands| {
+        commands.run_system_with_input(**seek, shown);
+    };
+    parent
+        .spawn((
+            ScrubNotch(shown),
+            Node {
+                flex_grow: 1.0,
+                ..Default::default()
+            },
+            BackgroundColor(Color::Srgba(SLATE_GRAY)),
+        ))
+        .observe(
+            move |_click: Trigger<Pointer<Click>>,
+                  seek_replay: Res<SeekReplayResource>,
+                  commands: Commands| {
+                seek(seek_replay, commands);
+            },
+        )
+        .observe(
+            move |_over: Trigger<Pointer<Over>>,
+                  mouse: Res<ButtonInput<MouseButton>>,
+                  seek_replay: Res<SeekReplayResource>,
+                  commands: Commands| {
+                if mouse.pressed(MouseButton::Left) {
+                    seek(seek_replay, commands);
+                }
+            },
+        );
+}
+
+fn update_replay_bar(
+    replay: Res<Replay>,
+    mut move_number: Single<&mut Text, With<MoveNumberText>>,
+    mut notation: Single<&mut Text, (With<NotationText>, Without<MoveNumberText>)>,
+    play: Single<&Children, With<PlayButton>>,
+    speed: Single<&Children, With<SpeedButton>>,
+    mut texts: Query<&mut Text, (Without<MoveNumberText>, Without<NotationText>)>,
+    mut notches: Query<(&ScrubNotch, &mut BackgroundColor)>,
+) {
+    ***move_number = move_number_label(&replay);
+    ***notation = notation_label(&replay);
+    **texts.get_mut(play[0]).unwrap() = play_label(&replay).to_string();
+    **texts.get_mut(speed[0]).unwrap() = speed_label(&replay);
+    for (&ScrubNotch(shown), mut color) in &mut notches {
+        let played = shown <= replay.shown;
+        color.0 = Color::Srgba(if played { GOLD } else { SLATE_GRAY });
+    }
+}
+
+fn move_number_label(replay: &Replay) -> String {
+    format!("Move {} of {}", replay.shown, replay.moves.len())
+}
+
+/// The last move played, written in move notation.
+fn notation_label(replay: &Replay) -> String {
+    match replay.shown.checked_sub(1) {
+        Some(last) => replay.moves[last].to_string(),
+        None => "Start".to_string(),
+    }
+}
+
+fn play_label(replay: &Replay) -> &'static str {
+    if replay.playing {
+        "Pause"
+    } else {
+        "Play"
+    }
+}
+
+fn speed_label(replay: &Replay) -> String {
+    format!("{}x", SPEEDS[replay.speed])
+}
@@ -0,0 +1,142 @@
+//! Keeps the game in progress in the preferences, so closing the window or backgrounding the app
+//! doesn't throw it away.
+//!
+//! Only the record of the game is needed to rebuild it, but the seeds and turn are stored as well,
+//! so a save that doesn't replay to the same board can be thrown out instead of resumed.
+
+use bevy::prelude::*;
+use bevy_prefs_lite::{Preferences, StartAutosaveTimer};
+use stones_rules::variants::{ChosenVariant, Index, Variant};
+
+use crate::game::{
+    actions::undo::Undo,
+    ai::{Computer, Players},
+    record::{GameRecord, GameStarted},
+    Board, Hole, Player,
+};
+
+const FILENAME: &str = "saved_game";
+const GROUP: &str = "game";
+
+/// A game to pick back up when play starts, instead of setting up a fresh board.
+#[derive(Debug, Resource)]
+pub struct ResumeGame {
+    pub variant: Box<dyn Variant>,
+    /// When the game was first started, in seconds since the Unix epoch.
+    pub started_at: u64,
+}
+
+/// Everything stored about the game in progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedGame {
+    pub record: GameRecord,
+    /// Whether moves may be taken back, as chosen when the game was started.
+    pub undo: Undo,
+    /// The seeds in every hole and then the store, for each player in turn.
+    board: Vec<usize>,
+    turn: usize,
+}
+
+impl SavedGame {
+    /// The saved game, if one is in progress.
+    pub fn load(prefs: &Preferences) -> Option<Self> {
+        let group = prefs.get(FILENAME)?.get_group(GROUP)?;
+        if !group.get::<bool>("in_progress")? {
+            return None;
+        }
+        let record = GameRecord::from_json(&group.get::<String>("record")?)
+            .inspect_err(|e| tracing::warn!("{e}"))
+            .ok()?;
+        // Games saved before undo could be chosen get the rule they were started with.
+        let undo = group
+            .get::<Undo>("undo")
+            .unwrap_or_else(|| Undo::for_players(&record.players(Computer::default())));
+        Some(Self {
+            record,
+            undo,
+            board: group.get("board")?,
+            turn: group.get("turn")?,
+        })
+    }
+
+    fn store(&self, prefs: &mut Preferences) {
+        let record = match self.record.to_json() {
+            Ok(record) => record,
+            Err(e) => {
+                tracing::warn!("{e}");
+                return;
+            }
+        };
+        let Some(mut file) = prefs.get_mut(FILENAME) else {
+            tracing::warn!("Unable to open preferences to save the game");
+            return;
+        };
+        let Some(mut group) = file.get_group_mut(GROUP) else {
+            tracing::warn!("Unable to open preferences to save the game");
+            return;
+        };
+        group.set("in_progress", true);
+        group.set("record", record);
+        group.set("undo", self.undo);
+        group.set("board", self.board.clone());
+        group.set("turn", self.turn);
+    }
+
+    /// Replays the saved moves, returning the variant it was played with and the game as it
+    /// stood, or `None` if the save doesn't add up.
+    pub fn restore(&self) -> Option<(ChosenVariant, ResumeGame)> {
+        let (chosen, variant) = self.record.replay()?;
+        let matches =
+            board_seeds(&*variant) == self.board && *variant.current_player() == self.turn;
+        matches.then_some((
+            chosen,
+            ResumeGame {
+                variant,
+                started_at: self.record.started_at,
+            },
+        ))
+    }
+}
+
+/// The seeds in every hole and then the store, for each player in turn.
+fn board_seeds(variant: &dyn Variant) -> Vec<usize> {
+    let holes = variant.position().side_hole_count();
+    (0..variant.player_count())
+        .flat_map(move |p| {
+            (0..holes)
+                .map(move |h| Index::Player(Player(p), Hole(h)))
+                .chain([Index::Score(Player(p))])
+        })
+        .map(|index| variant.seeds(index))
+        .collect()
+}
+
+/// Stores the game as it stands, once the last move has finished.
+pub fn save_game(world: &mut World) {
+    let board = world.resource::<Board>();
+    let saved = SavedGame {
+        record: GameRecord::new(
+            world.resource::<ChosenVariant>(),
+            board,
+            world.resource::<Players>(),
+            **world.resource::<GameStarted>(),
+        ),
+        undo: *world.resource::<Undo>(),
+        board: board_seeds(&***board),
+        turn: *board.current_player(),
+    };
+    saved.store(&mut world.resource_mut::<Preferences>());
+    world.commands().queue(StartAutosaveTimer);
+}
+
+/// Forgets the saved game, so there's nothing to continue once it's over.
+pub fn clear_saved_game(prefs: &mut Preferences, commands: &mut Commands) {
+    let Some(mut file) = prefs.get_mut(FILENAME) else {
+        return;
+    };
+    let Some(mut group) = file.get_group_mut(GROUP) else {
+        return;
+    };
+    group.set("in_progress", false);
+    commands.queue(StartAutosaveTimer);
+}
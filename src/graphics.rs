@@ -3,16 +3,20 @@ use std::f32::consts::{FRAC_PI_2, PI};
 use avian3d::prelude::PhysicsPickable;
 use bevy::prelude::*;
 
+use crate::camera::OrbitCamera;
+
 pub(crate) fn setup_graphics(mut commands: Commands, cameras: Query<Entity, With<Camera>>) {
     if cameras.iter().count() > 0 {
         return;
     }
 
+    let orbit = OrbitCamera::default();
     commands.spawn((
         Name::new("Primary Camera"),
         Camera3d::default(),
         PhysicsPickable,
-        Transform::from_xyz(0.0, 0.45, 0.45).looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y),
+        orbit.transform(),
+        orbit,
         #[cfg(target_os = "android")]
         Msaa::Off,
     ));
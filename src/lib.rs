@@ -16,16 +16,19 @@ use iyes_progress::ProgressPlugin;
 use tracing::Level;
 
 pub(crate) mod assets;
+pub(crate) mod audio;
+pub(crate) mod camera;
 pub(crate) mod events;
 pub(crate) mod game;
 pub(crate) mod graphics;
 pub(crate) mod loading;
 pub(crate) mod physics;
-pub(crate) mod rules;
 pub(crate) mod ui;
 
-/// The number of players in the game.
-pub const PLAYER_COUNT: usize = 2;
+pub use stones_rules::MAX_PLAYER_COUNT;
+
+/// The name the game's preferences and records are kept under on the platform.
+pub const APP_NAME: &str = "org.favil.stones";
 
 /// The Game Plugin that loads all the other bevy plugins.
 pub struct GamePlugin;
@@ -47,7 +50,7 @@ impl Plugin for GamePlugin {
                 let (config, _) = config_store.config::<PhysicsGizmos>();
                 config.enabled
             }))
-            .insert_resource(Preferences::new("org.favil.stones"))
+            .insert_resource(Preferences::new(APP_NAME))
             // .add_plugins(BlenvyPlugin::default())
             ;
         }
@@ -84,6 +87,8 @@ impl Plugin for GamePlugin {
         .add_plugins((
             loading::Plugin,
             assets::Plugin,
+            audio::Plugin,
+            camera::Plugin,
             events::Plugin,
             game::Plugin,
             ui::Plugin,
@@ -1,29 +1,75 @@
+use std::ops::RangeInclusive;
+
 use bevy::{
     app::{self, AppExit},
     color::palettes::css::GOLD,
+    ecs::system::IntoObserverSystem,
     prelude::*,
 };
+use bevy_prefs_lite::Preferences;
+use stones_rules::variants::ChosenVariant;
+
+use crate::{
+    assets::GameAssets,
+    game::{
+        actions::undo::Undo,
+        ai::{Computer, Players},
+        record::GameRecord,
+        save::{ResumeGame, SavedGame},
+        GameState,
+    },
+};
 
-use crate::{assets::GameAssets, game::GameState};
+pub mod focus;
+mod new_game;
+mod records;
+pub mod settings;
 
 pub struct Plugin;
 
 impl app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), setup_menu);
+        app.add_plugins((
+            focus::Plugin,
+            new_game::Plugin,
+            records::Plugin,
+            settings::Plugin,
+        ))
+        .add_systems(OnEnter(GameState::Menu), setup_menu);
     }
 }
 
 #[derive(Debug, Clone, Copy, Component)]
 pub struct Menu;
 
+#[derive(Debug, Clone, Copy, Component)]
+struct ContinueButton;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct ReviewButton;
+
 #[derive(Debug, Clone, Copy, Component)]
 struct StartButton;
 
+#[derive(Debug, Clone, Copy, Component)]
+struct SettingsButton;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct SettingButton;
+
 #[derive(Debug, Clone, Copy, Component)]
 struct QuitButton;
 
-fn setup_menu(mut commands: Commands, game_assets: Res<GameAssets>) {
+fn setup_menu(mut commands: Commands, game_assets: Res<GameAssets>, prefs: Res<Preferences>) {
+    let saved_game = SavedGame::load(&prefs).and_then(|saved| {
+        let restored = saved.restore();
+        if restored.is_none() {
+            tracing::warn!("Ignoring a saved game that doesn't replay: {saved:?}");
+        }
+        Some(saved).zip(restored)
+    });
+    let any_records = !GameRecord::list().is_empty();
+
     commands
         .spawn((
             Name::new("MainMenu"),
@@ -45,6 +91,13 @@ fn setup_menu(mut commands: Commands, game_assets: Res<GameAssets>) {
                 TextFont::from_font(game_assets.main_font.clone()).with_font_size(60.0),
                 TextColor(Color::WHITE),
             ));
+            let font = &game_assets.main_font;
+            if let Some((saved, restored)) = saved_game {
+                spawn_resume_button(parent, font, saved.record, saved.undo, restored);
+            }
+            if any_records {
+                spawn_review_button(parent, font);
+            }
             parent
                 .spawn((StartButton, Button, BackgroundColor(Color::NONE)))
                 .observe(hover_button(Color::Srgba(GOLD)))
@@ -52,12 +105,29 @@ fn setup_menu(mut commands: Commands, game_assets: Res<GameAssets>) {
                 .observe(
                     |_trigger: Trigger<Pointer<Click>>,
                      mut next_state: ResMut<NextState<GameState>>| {
-                        next_state.set(GameState::Playing);
+                        next_state.set(GameState::NewGame);
                     },
                 )
                 .with_children(|parent| {
                     parent.spawn((
-                        Text::new("Start Game"),
+                        Text::new("New Game"),
+                        TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
+                        TextColor(Color::WHITE),
+                    ));
+                });
+            parent
+                .spawn((SettingsButton, Button, BackgroundColor(Color::NONE)))
+                .observe(hover_button(Color::Srgba(GOLD)))
+                .observe(unhover_button(Color::WHITE))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>,
+                     mut next_state: ResMut<NextState<GameState>>| {
+                        next_state.set(GameState::Settings);
+                    },
+                )
+                .with_children(|parent| {
+                    parent.spawn((
+                        Text::new("Settings"),
                         TextFont::from_font(game_assets.main_font.clone()).with_font_size(40.0),
                         TextColor(Color::WHITE),
                     ));
@@ -81,6 +151,131 @@ fn setup_menu(mut commands: Commands, game_assets: Res<GameAssets>) {
         });
 }
 
+/// Spawns a menu button that picks the saved game back up where it left off.
+fn spawn_resume_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    record: GameRecord,
+    saved_undo: Undo,
+    restored: (ChosenVariant, ResumeGame),
+) {
+    let mut restored = Some(restored);
+    parent
+        .spawn((ContinueButton, Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::WHITE))
+        .observe(
+            move |_trigger: Trigger<Pointer<Click>>,
+                  computer: Res<Computer>,
+                  mut players: ResMut<Players>,
+                  mut undo: ResMut<Undo>,
+                  mut commands: Commands,
+                  mut next_state: ResMut<NextState<GameState>>| {
+                let Some((chosen, resume)) = restored.take() else {
+                    return;
+                };
+                *players = record.players(*computer);
+                *undo = saved_undo;
+                commands.insert_resource(chosen);
+                commands.insert_resource(resume);
+                next_state.set(GameState::Playing);
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Continue"),
+                TextFont::from_font(font.clone()).with_font_size(40.0),
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Spawns a menu button that lists the recorded games to pick one to replay.
+fn spawn_review_button(parent: &mut ChildBuilder, font: &Handle<Font>) {
+    parent
+        .spawn((ReviewButton, Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::WHITE))
+        .observe(
+            |_trigger: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<GameState>>| {
+                next_state.set(GameState::Records);
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Review a Game"),
+                TextFont::from_font(font.clone()).with_font_size(40.0),
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Spawns a smaller menu button, for changing one of the game's settings.
+fn spawn_setting<M>(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: String,
+    on_click: impl IntoObserverSystem<Pointer<Click>, (), M>,
+) {
+    parent
+        .spawn((SettingButton, Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::WHITE))
+        .observe(on_click)
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont::from_font(font.clone()).with_font_size(30.0),
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// Spawns a setting that applies `change` to the resource when clicked, which lays the screen out
+/// again.
+fn spawn_option<R: Resource>(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: String,
+    change: fn(&mut R),
+) {
+    spawn_setting(
+        parent,
+        font,
+        label,
+        move |_click: Trigger<Pointer<Click>>, mut setting: ResMut<R>| change(&mut setting),
+    );
+}
+
+fn spawn_button<M>(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    label: &str,
+    on_click: impl IntoObserverSystem<Pointer<Click>, (), M>,
+) {
+    parent
+        .spawn((Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(Color::WHITE))
+        .observe(on_click)
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(label),
+                TextFont::from_font(font.clone()).with_font_size(40.0),
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+/// The count after `current`, wrapping around to the start of `range`.
+fn next_in(range: RangeInclusive<usize>, current: usize) -> usize {
+    if current < *range.end() {
+        current + 1
+    } else {
+        *range.start()
+    }
+}
+
 pub fn hover_button(
     new_color: Color,
 ) -> impl FnMut(Trigger<Pointer<Over>>, Query<&Children>, Query<&mut TextColor>) {
@@ -0,0 +1,280 @@
+//! Moving between buttons with the keyboard or a gamepad, for playing without a pointer.
+//!
+//! The focused button is sent the same pointer events the mouse would send it, so it lights up and
+//! does what it does when clicked without needing to know where the events came from.
+
+use core::time::Duration;
+
+use bevy::{
+    app,
+    ecs::system::SystemParam,
+    picking::{
+        backend::HitData,
+        pointer::{Location, PointerButton, PointerId},
+    },
+    prelude::*,
+    render::camera::NormalizedRenderTarget,
+    window::{PrimaryWindow, WindowRef},
+};
+
+use crate::{
+    camera::OrbitCamera,
+    game::{GameState, InGame},
+};
+
+/// How far a stick has to be pushed to count as a press.
+const STICK_PRESS: f32 = 0.6;
+
+/// How far a stick has to come back before it can be pressed again.
+const STICK_RELEASE: f32 = 0.3;
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MenuFocus>().add_systems(
+            Update,
+            (
+                clear_menu_focus.run_if(state_changed::<GameState>),
+                move_menu_focus,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// The keys, buttons and sticks for moving a focus around and choosing what it's on.
+#[derive(SystemParam)]
+pub struct Navigation<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    /// Whether a stick is still pushed from last time, so holding it only moves once.
+    stick_pushed: Local<'s, bool>,
+}
+
+impl Navigation<'_, '_> {
+    /// Which way the arrow keys, a d-pad or a left stick were just pushed, with up as positive.
+    pub fn direction(&mut self) -> Option<Vec2> {
+        let keys = [
+            (KeyCode::ArrowUp, GamepadButton::DPadUp, Vec2::Y),
+            (KeyCode::ArrowDown, GamepadButton::DPadDown, Vec2::NEG_Y),
+            (KeyCode::ArrowLeft, GamepadButton::DPadLeft, Vec2::NEG_X),
+            (KeyCode::ArrowRight, GamepadButton::DPadRight, Vec2::X),
+        ];
+        let pressed = keys.into_iter().find_map(|(key, button, direction)| {
+            let pressed = self.keys.just_pressed(key)
+                || self
+                    .gamepads
+                    .iter()
+                    .any(|gamepad| gamepad.just_pressed(button));
+            pressed.then_some(direction)
+        });
+        if pressed.is_some() {
+            return pressed;
+        }
+
+        let stick = self
+            .gamepads
+            .iter()
+            .map(Gamepad::left_stick)
+            .max_by(|a, b| a.length().total_cmp(&b.length()))
+            .unwrap_or_default();
+        if stick.length() < STICK_RELEASE {
+            *self.stick_pushed = false;
+        }
+        if *self.stick_pushed || stick.length() < STICK_PRESS {
+            return None;
+        }
+        *self.stick_pushed = true;
+        // Only the way it was pushed furthest, so a slightly crooked push doesn't go astray.
+        Some(if stick.x.abs() > stick.y.abs() {
+            Vec2::new(stick.x.signum(), 0.0)
+        } else {
+            Vec2::new(0.0, stick.y.signum())
+        })
+    }
+
+    /// Whether Tab, or a gamepad's north button, was just pressed to step through the buttons,
+    /// and which way.
+    fn tabbed(&self) -> Option<isize> {
+        let shift = self
+            .keys
+            .any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+        if self.keys.just_pressed(KeyCode::Tab) {
+            return Some(if shift { -1 } else { 1 });
+        }
+        self.gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::North))
+            .then_some(1)
+    }
+
+    /// Whether Enter, Space or a gamepad's south button was just pressed.
+    pub fn confirmed(&self) -> bool {
+        self.keys
+            .any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter, KeyCode::Space])
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(GamepadButton::South))
+    }
+
+    /// Whether Escape, or a gamepad's east button, was just pressed.
+    fn cancelled(&self) -> bool {
+        self.keys.just_pressed(KeyCode::Escape)
+            || self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(GamepadButton::East))
+    }
+}
+
+/// The button being pointed at with the keyboard or a gamepad, if any.
+#[derive(Debug, Default, Clone, Copy, Resource)]
+pub struct MenuFocus {
+    button: Option<Entity>,
+    /// Where the button is among the ones on screen, to land on the same place again when the
+    /// screen is laid out afresh.
+    place: usize,
+}
+
+impl MenuFocus {
+    /// Whether a button has the focus, rather than the board.
+    pub fn is_active(&self) -> bool {
+        self.button.is_some()
+    }
+}
+
+/// Sends a button the pointer events the mouse would send it.
+#[derive(SystemParam)]
+struct FakePointer<'w, 's> {
+    window: Single<'w, Entity, With<PrimaryWindow>>,
+    camera: Single<'w, Entity, With<OrbitCamera>>,
+    commands: Commands<'w, 's>,
+}
+
+impl FakePointer<'_, '_> {
+    fn send<E>(&mut self, target: Entity, position: Vec2, event: E)
+    where
+        E: core::fmt::Debug + Clone + Reflect,
+    {
+        let Some(window) = WindowRef::Entity(*self.window).normalize(None) else {
+            return;
+        };
+        self.commands.trigger_targets(
+            Pointer {
+                target,
+                pointer_id: PointerId::Mouse,
+                pointer_location: Location {
+                    target: NormalizedRenderTarget::Window(window),
+                    position,
+                },
+                event,
+            },
+            target,
+        );
+    }
+
+    fn hit(&self) -> HitData {
+        HitData {
+            camera: *self.camera,
+            depth: 0.0,
+            position: None,
+            normal: None,
+        }
+    }
+}
+
+/// Starts afresh on each screen, rather than carrying the focus over from the last one.
+fn clear_menu_focus(mut focus: ResMut<MenuFocus>) {
+    *focus = MenuFocus::default();
+}
+
+/// Steps the focus through the buttons on screen, top to bottom and left to right, and clicks the
+/// focused one when confirmed.
+///
+/// Everywhere but the board, the arrow keys, d-pad and stick move the focus too. On the board they
+/// are left for choosing holes, so Tab and the north button are the way to the buttons there, and
+/// Escape or the east button go back to the board.
+fn move_menu_focus(
+    mut navigation: Navigation,
+    mut focus: ResMut<MenuFocus>,
+    buttons: Query<(Entity, &GlobalTransform, &InheritedVisibility), With<Button>>,
+    in_game: Option<Res<State<InGame>>>,
+    mut pointer: FakePointer,
+) {
+    let mut visible = buttons
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(button, transform, _)| (button, transform.translation().truncate()))
+        .collect::<Vec<_>>();
+    visible.sort_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+
+    let step = if in_game.is_some() {
+        navigation.tabbed()
+    } else {
+        navigation.tabbed().or_else(|| {
+            navigation.direction().map(|direction| {
+                if direction.y > 0.0 || direction.x < 0.0 {
+                    -1
+                } else {
+                    1
+                }
+            })
+        })
+    };
+    let current = focus
+        .button
+        .and_then(|button| visible.iter().position(|&(b, _)| b == button));
+    let hit = pointer.hit();
+
+    // The focused button went away with nothing to take its place.
+    if current.is_none() && visible.is_empty() {
+        *focus = MenuFocus::default();
+        return;
+    }
+
+    if in_game.is_some() && navigation.cancelled() {
+        if let Some(place) = current {
+            let (button, position) = visible[place];
+            pointer.send(button, position, Out { hit });
+        }
+        *focus = MenuFocus::default();
+        return;
+    }
+
+    let next = match (step, current) {
+        (Some(step), Some(place)) => Some(place as isize + step),
+        (Some(step), None) if focus.button.is_none() => Some(if step > 0 { 0 } else { -1 }),
+        // The screen was laid out again, so the same place is focused on the new one.
+        (_, None) if focus.button.is_some() => Some(focus.place.min(visible.len() - 1) as isize),
+        _ => None,
+    };
+    if let Some(next) = next {
+        if let Some(place) = current {
+            let (button, position) = visible[place];
+            pointer.send(button, position, Out { hit: hit.clone() });
+        }
+        let place = next.rem_euclid(visible.len() as isize) as usize;
+        let (button, position) = visible[place];
+        pointer.send(button, position, Over { hit });
+        *focus = MenuFocus {
+            button: Some(button),
+            place,
+        };
+        return;
+    }
+
+    if let (true, Some(place)) = (navigation.confirmed(), current) {
+        let (button, position) = visible[place];
+        pointer.send(
+            button,
+            position,
+            Click {
+                button: PointerButton::Primary,
+                hit: pointer.hit(),
+                duration: Duration::ZERO,
+            },
+        );
+    }
+}
@@ -0,0 +1,598 @@
+//! The screen for setting up a new game: which variant to play and how, who against, and who
+//! moves first.
+
+use std::time::Duration;
+
+use bevy::{
+    app,
+    color::palettes::css::{GOLD, SILVER},
+    prelude::*,
+};
+use bevy_prefs_lite::Preferences;
+use stones_rules::variants::{
+    custom::{Capture, Custom},
+    kalah::{self, Kalah, Sweep},
+    oware::{GrandSlam, Oware},
+    ChosenVariant, Variant,
+};
+
+use crate::{
+    assets::{GameAssets, VariantDefinition},
+    game::{
+        actions::undo::Undo,
+        ai::{Computer, Difficulty, Engine, PlayerKind, Players},
+        record::{now, SharedPosition, VariantRecord},
+        save::ResumeGame,
+        GameState, Player, PLAYER_NAMES,
+    },
+    MAX_PLAYER_COUNT,
+};
+
+use super::{
+    hover_button, next_in,
+    settings::{load_settings, store_settings},
+    spawn_button, spawn_option, spawn_setting, unhover_button,
+};
+
+const GROUP: &str = "new_game";
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Opponent::default())
+            .insert_resource(UndoChoice::default())
+            .add_systems(Startup, load_new_game)
+            .add_systems(OnEnter(GameState::NewGame), spawn_new_game_screen)
+            .add_systems(
+                Update,
+                spawn_new_game_screen.run_if(
+                    in_state(GameState::NewGame).and(
+                        resource_changed::<ChosenVariant>
+                            .or(resource_changed::<Opponent>)
+                            .or(resource_changed::<UndoChoice>)
+                            .or(resource_changed::<Computer>),
+                    ),
+                ),
+            );
+    }
+}
+
+/// Who the player nearest the camera plays against.
+#[derive(Debug, Default, Clone, Copy, Resource, PartialEq, Eq, Hash)]
+pub enum Opponent {
+    /// Someone else, taking turns on the same device.
+    #[default]
+    Human,
+    /// The computer, playing the far side of the board.
+    Computer,
+}
+
+impl Opponent {
+    fn next(self) -> Self {
+        match self {
+            Opponent::Human => Opponent::Computer,
+            Opponent::Computer => Opponent::Human,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Opponent::Human => "Human",
+            Opponent::Computer => "Computer",
+        }
+    }
+
+    /// Whether moves may be taken back against this opponent, until another rule is chosen.
+    fn undo(self) -> Undo {
+        match self {
+            Opponent::Human => Undo::Unlimited,
+            Opponent::Computer => Undo::Off,
+        }
+    }
+}
+
+/// Whether moves may be taken back in the game being set up.
+#[derive(Debug, Clone, Copy, Resource, Deref, DerefMut, PartialEq, Eq, Hash)]
+struct UndoChoice(Undo);
+
+impl Default for UndoChoice {
+    fn default() -> Self {
+        Self(Opponent::default().undo())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
+struct NewGameScreen;
+
+#[derive(Debug, Clone, Copy, Component)]
+struct VariantButton;
+
+/// Lays out the screen from the current choices, replacing it if it's already there.
+fn spawn_new_game_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<NewGameScreen>>,
+    game_assets: Res<GameAssets>,
+    definitions: Res<Assets<VariantDefinition>>,
+    chosen: Res<ChosenVariant>,
+    opponent: Res<Opponent>,
+    undo: Res<UndoChoice>,
+    computer: Res<Computer>,
+) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let font = &game_assets.main_font;
+    commands
+        .spawn((
+            Name::new("NewGame"),
+            NewGameScreen,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 1.0)),
+            StateScoped(GameState::NewGame),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("New Game"),
+                TextFont::from_font(font.clone()).with_font_size(60.0),
+                TextColor(Color::WHITE),
+            ));
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(40.0),
+                    ..Default::default()
+                })
+                .with_children(|parent| {
+                    let custom = game_assets
+                        .variants
+                        .iter()
+                        .filter_map(|handle| definitions.get(handle))
+                        .filter_map(|definition| Custom::new((**definition).clone()).ok())
+                        .map(ChosenVariant::Custom);
+                    for variant in ChosenVariant::all().into_iter().chain(custom) {
+                        spawn_variant_button(parent, font, variant, &chosen);
+                    }
+                });
+            parent.spawn((
+                Text::new(chosen.description()),
+                TextFont::from_font(font.clone()).with_font_size(24.0),
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    max_width: Val::Percent(60.0),
+                    margin: UiRect::vertical(Val::Px(10.0)),
+                    ..Default::default()
+                },
+            ));
+
+            match *chosen {
+                ChosenVariant::Kalah(ref game) => {
+                    spawn_option(
+                        parent,
+                        font,
+                        format!("Holes: {}", game.hole_count()),
+                        |chosen: &mut ChosenVariant| {
+                            change_kalah(chosen, |game| {
+                                let holes = next_in(kalah::HOLE_COUNTS, game.hole_count());
+                                Kalah::new(holes, game.starting_pieces(), game.sweep())
+                            });
+                        },
+                    );
+                    spawn_option(
+                        parent,
+                        font,
+                        format!("Seeds: {}", game.starting_pieces()),
+                        |chosen: &mut ChosenVariant| {
+                            change_kalah(chosen, |game| {
+                                let seeds = next_in(kalah::SEED_COUNTS, game.starting_pieces());
+                                Kalah::new(game.hole_count(), seeds, game.sweep())
+                            });
+                        },
+                    );
+                    spawn_option(
+                        parent,
+                        font,
+                        format!("Leftover Seeds: {}", sweep_name(game.sweep())),
+                        |chosen: &mut ChosenVariant| {
+                            change_kalah(chosen, |game| {
+                                let sweep = next_sweep(game.sweep());
+                                Kalah::new(game.hole_count(), game.starting_pieces(), sweep)
+                            });
+                        },
+                    );
+                }
+                ChosenVariant::Oware(ref game) => {
+                    spawn_option(
+                        parent,
+                        font,
+                        format!("Grand Slam: {}", grand_slam_name(game.grand_slam())),
+                        |chosen: &mut ChosenVariant| {
+                            let ChosenVariant::Oware(game) = chosen else {
+                                return;
+                            };
+                            let first = game.first_player();
+                            *game = Oware::with_grand_slam(next_grand_slam(game.grand_slam()))
+                                .with_first_player(first);
+                        },
+                    );
+                }
+                // Variant files fix every rule, so they're only shown.
+                ChosenVariant::Custom(ref game) => {
+                    let definition = game.definition();
+                    spawn_rule(parent, font, format!("Holes: {}", definition.holes));
+                    spawn_rule(parent, font, format!("Seeds: {}", definition.seeds));
+                    let captures = match definition.captures.as_slice() {
+                        [] => "None".to_string(),
+                        captures => captures
+                            .iter()
+                            .map(capture_name)
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    };
+                    spawn_rule(parent, font, format!("Captures: {captures}"));
+                    let grand_slam = definition.grand_slam.map_or("Ordinary", grand_slam_name);
+                    spawn_rule(parent, font, format!("Grand Slam: {grand_slam}"));
+                    let must_feed = if definition.must_feed { "Yes" } else { "No" };
+                    spawn_rule(parent, font, format!("Must Feed: {must_feed}"));
+                }
+            }
+
+            spawn_option(
+                parent,
+                font,
+                format!("First Move: {}", PLAYER_NAMES[*chosen.first_player()]),
+                |chosen: &mut ChosenVariant| {
+                    let next = Player::next(chosen.first_player(), chosen.player_count());
+                    chosen.set_first_player(next);
+                },
+            );
+            spawn_setting(
+                parent,
+                font,
+                format!("Opponent: {}", opponent.name()),
+                |_click: Trigger<Pointer<Click>>,
+                 mut opponent: ResMut<Opponent>,
+                 mut undo: ResMut<UndoChoice>| {
+                    *opponent = opponent.next();
+                    **undo = opponent.undo();
+                },
+            );
+            if *opponent == Opponent::Computer {
+                spawn_computer_options(parent, font, &computer);
+            }
+            spawn_option(
+                parent,
+                font,
+                format!("Undo: {}", undo.name()),
+                |undo: &mut UndoChoice| **undo = undo.next(),
+            );
+
+            spawn_button(parent, font, "Start", start_game);
+            if let Some(shared) = set_up_shared_position(&chosen) {
+                spawn_shared_position_button(parent, font, shared);
+            }
+            spawn_button(
+                parent,
+                font,
+                "Back",
+                |_click: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<GameState>>| {
+                    next_state.set(GameState::Menu);
+                },
+            );
+        });
+}
+
+/// Spawns a button that switches to `variant`, played with its standard rules.
+fn spawn_variant_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    variant: ChosenVariant,
+    chosen: &ChosenVariant,
+) {
+    let name = variant.name().to_string();
+    let color = if variant.same_variant(chosen) {
+        Color::Srgba(GOLD)
+    } else {
+        Color::WHITE
+    };
+    let mut variant = Some(variant);
+    parent
+        .spawn((VariantButton, Button, BackgroundColor(Color::NONE)))
+        .observe(hover_button(Color::Srgba(GOLD)))
+        .observe(unhover_button(color))
+        .observe(
+            move |_click: Trigger<Pointer<Click>>, mut chosen: ResMut<ChosenVariant>| {
+                let Some(mut variant) = variant.take_if(|variant| !variant.same_variant(&chosen))
+                else {
+                    return;
+                };
+                variant.set_first_player(chosen.first_player());
+                *chosen = variant;
+            },
+        )
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(name),
+                TextFont::from_font(font.clone()).with_font_size(40.0),
+                TextColor(color),
+            ));
+        });
+}
+
+/// Spawns a rule of the variant that can't be changed, in the place of a setting.
+fn spawn_rule(parent: &mut ChildBuilder, font: &Handle<Font>, label: String) {
+    parent.spawn((
+        Text::new(label),
+        TextFont::from_font(font.clone()).with_font_size(30.0),
+        TextColor(Color::Srgba(SILVER)),
+    ));
+}
+
+/// Spawns the settings for how the computer plays.
+fn spawn_computer_options(parent: &mut ChildBuilder, font: &Handle<Font>, computer: &Computer) {
+    spawn_option(
+        parent,
+        font,
+        format!("Engine: {}", computer.engine.name()),
+        |c: &mut Computer| c.engine = c.engine.next(),
+    );
+    match computer.engine {
+        Engine::Minimax => {
+            spawn_option(
+                parent,
+                font,
+                format!("Difficulty: {}", computer.difficulty.name()),
+                |c: &mut Computer| c.difficulty = c.difficulty.next(),
+            );
+        }
+        Engine::MonteCarlo => {
+            spawn_option(
+                parent,
+                font,
+                format!("Iterations: {}", computer.iterations),
+                Computer::next_iterations,
+            );
+            spawn_option(
+                parent,
+                font,
+                format!("Time Limit: {:.1}s", computer.time_limit.as_secs_f32()),
+                Computer::next_time_limit,
+            );
+        }
+    }
+}
+
+/// Replaces the Kalah game being set up with `change(game)`, keeping who moves first.
+fn change_kalah(chosen: &mut ChosenVariant, change: fn(&Kalah) -> Kalah) {
+    if let ChosenVariant::Kalah(game) = chosen {
+        let first = game.first_player();
+        *game = change(game).with_first_player(first);
+    }
+}
+
+fn next_sweep(sweep: Sweep) -> Sweep {
+    match sweep {
+        Sweep::ToOwner => Sweep::ToMover,
+        Sweep::ToMover => Sweep::ToOwner,
+    }
+}
+
+fn sweep_name(sweep: Sweep) -> &'static str {
+    match sweep {
+        Sweep::ToOwner => "To Owner",
+        Sweep::ToMover => "To Mover",
+    }
+}
+
+fn next_grand_slam(grand_slam: GrandSlam) -> GrandSlam {
+    match grand_slam {
+        GrandSlam::NoCapture => GrandSlam::Capture,
+        GrandSlam::Capture => GrandSlam::Forbidden,
+        GrandSlam::Forbidden => GrandSlam::NoCapture,
+    }
+}
+
+fn capture_name(capture: &Capture) -> String {
+    match capture {
+        Capture::Opposite { .. } => "Opposite".to_string(),
+        Capture::Counts {
+            counts, chained, ..
+        } => {
+            let counts = counts
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" or ");
+            if *chained {
+                format!("{counts} Chained")
+            } else {
+                counts
+            }
+        }
+    }
+}
+
+fn grand_slam_name(grand_slam: GrandSlam) -> &'static str {
+    match grand_slam {
+        GrandSlam::NoCapture => "No Capture",
+        GrandSlam::Capture => "Capture",
+        GrandSlam::Forbidden => "Forbidden",
+    }
+}
+
+/// The chosen variant started from the shared position, and the game as it stands once the
+/// position's moves are played, or `None` if there's no position to share or it doesn't fit.
+fn set_up_shared_position(chosen: &ChosenVariant) -> Option<(ChosenVariant, Box<dyn Variant>)> {
+    let shared = SharedPosition::load()?;
+    let mut chosen = VariantRecord::new(chosen).to_chosen()?;
+    let variant = shared.set_up(&mut chosen)?;
+    Some((chosen, variant))
+}
+
+/// Spawns a button that starts the game from the shared position instead of the opening one.
+fn spawn_shared_position_button(
+    parent: &mut ChildBuilder,
+    font: &Handle<Font>,
+    shared: (ChosenVariant, Box<dyn Variant>),
+) {
+    let mut shared = Some(shared);
+    spawn_button(
+        parent,
+        font,
+        "Start From Position",
+        move |_click: Trigger<Pointer<Click>>,
+              opponent: Res<Opponent>,
+              choice: Res<UndoChoice>,
+              computer: Res<Computer>,
+              mut players: ResMut<Players>,
+              mut undo: ResMut<Undo>,
+              mut prefs: ResMut<Preferences>,
+              mut commands: Commands,
+              mut next_state: ResMut<NextState<GameState>>| {
+            let Some((chosen, variant)) = shared.take() else {
+                return;
+            };
+            seat_players(*opponent, *computer, &mut players);
+            *undo = **choice;
+            store_new_game(
+                &mut prefs,
+                &mut commands,
+                &chosen,
+                *opponent,
+                *undo,
+                *computer,
+            );
+            commands.insert_resource(chosen);
+            commands.insert_resource(ResumeGame {
+                variant,
+                started_at: now(),
+            });
+            next_state.set(GameState::Playing);
+        },
+    );
+}
+
+/// Starts a game with the chosen settings, remembering them for next time.
+fn start_game(
+    _click: Trigger<Pointer<Click>>,
+    mut chosen: ResMut<ChosenVariant>,
+    opponent: Res<Opponent>,
+    choice: Res<UndoChoice>,
+    computer: Res<Computer>,
+    mut players: ResMut<Players>,
+    mut undo: ResMut<Undo>,
+    mut prefs: ResMut<Preferences>,
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // The last game may have been started from a shared position, so go back to the opening.
+    if let Some(opening) = VariantRecord::new(&chosen).to_chosen() {
+        if opening.position() != chosen.position() {
+            *chosen = opening;
+        }
+    }
+    seat_players(*opponent, *computer, &mut players);
+    *undo = **choice;
+    store_new_game(
+        &mut prefs,
+        &mut commands,
+        &chosen,
+        *opponent,
+        *undo,
+        *computer,
+    );
+    next_state.set(GameState::Playing);
+}
+
+/// Sits the computer or another person opposite the player nearest the camera.
+fn seat_players(opponent: Opponent, computer: Computer, players: &mut Players) {
+    match opponent {
+        Opponent::Human => {
+            **players = [PlayerKind::Human; MAX_PLAYER_COUNT];
+        }
+        Opponent::Computer => {
+            // The player nearest the camera is always the human, and the computer plays the rest.
+            **players = core::array::from_fn(|p| match p {
+                1 => PlayerKind::Human,
+                _ => PlayerKind::Computer(computer),
+            });
+        }
+    }
+}
+
+fn store_new_game(
+    prefs: &mut Preferences,
+    commands: &mut Commands,
+    chosen: &ChosenVariant,
+    opponent: Opponent,
+    undo: Undo,
+    computer: Computer,
+) {
+    let variant = match serde_json::to_string(&VariantRecord::new(chosen)) {
+        Ok(variant) => variant,
+        Err(e) => {
+            tracing::warn!("{e}");
+            return;
+        }
+    };
+    let what = "the new game settings";
+    store_settings(prefs, commands, GROUP, what, |group| {
+        group.set("variant", variant);
+        group.set("against_computer", opponent == Opponent::Computer);
+        group.set("undo", undo);
+        group.set("engine", computer.engine);
+        group.set("difficulty", computer.difficulty);
+        group.set("iterations", computer.iterations);
+        group.set("time_limit_ms", computer.time_limit.as_millis() as u64);
+    });
+}
+
+/// Picks up the settings the last new game was started with.
+fn load_new_game(
+    prefs: Res<Preferences>,
+    mut chosen: ResMut<ChosenVariant>,
+    mut opponent: ResMut<Opponent>,
+    mut undo: ResMut<UndoChoice>,
+    mut computer: ResMut<Computer>,
+) {
+    load_settings(&prefs, GROUP, |group| {
+        let remembered = group
+            .get::<String>("variant")
+            .and_then(|variant| serde_json::from_str::<VariantRecord>(&variant).ok())
+            .and_then(VariantRecord::to_chosen);
+        if let Some(remembered) = remembered {
+            *chosen = remembered;
+        }
+        if let Some(against_computer) = group.get::<bool>("against_computer") {
+            *opponent = if against_computer {
+                Opponent::Computer
+            } else {
+                Opponent::Human
+            };
+        }
+        **undo = group.get::<Undo>("undo").unwrap_or(opponent.undo());
+        if let Some(engine) = group.get::<Engine>("engine") {
+            computer.engine = engine;
+        }
+        if let Some(difficulty) = group.get::<Difficulty>("difficulty") {
+            computer.difficulty = difficulty;
+        }
+        if let Some(iterations) = group.get::<u32>("iterations") {
+            computer.iterations = iterations;
+        }
+        if let Some(millis) = group.get::<u64>("time_limit_ms") {
+            computer.time_limit = Duration::from_millis(millis);
+        }
+    });
+}
@@ -0,0 +1,199 @@
+//! The screen for picking a recorded game to play through again, newest first.
+
+use std::path::Path;
+
+use bevy::{app, color::palettes::css::SILVER, prelude::*};
+use stones_rules::variants::Outcome;
+
+use crate::{
+    assets::GameAssets,
+    game::{
+        record::{now, records_dir, GameRecord},
+        replay::Replay,
+        GameState, PLAYER_NAMES,
+    },
+};
+
+use super::spawn_button;
+
+/// How many records are listed at a time.
+const PAGE_SIZE: usize = 6;
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RecordPage::default())
+            .add_systems(OnEnter(GameState::Records), spawn_records_screen)
+            .add_systems(
+                Update,
+                spawn_records_screen
+                    .run_if(in_state(GameState::Records).and(resource_changed::<RecordPage>)),
+            );
+    }
+}
+
+/// Which page of records is listed, counting from the newest.
+#[derive(Debug, Default, Clone, Copy, Resource, Deref, DerefMut)]
+pub struct RecordPage(usize);
+
+#[derive(Debug, Clone, Copy, Component)]
+struct RecordsScreen;
+
+/// Lays out the current page of records, replacing it if it's already there.
+fn spawn_records_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<RecordsScreen>>,
+    game_assets: Res<GameAssets>,
+    mut page: ResMut<RecordPage>,
+) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let paths = GameRecord::list();
+    let pages = paths.len().div_ceil(PAGE_SIZE).max(1);
+    // Records may have been removed since the page was last shown.
+    if **page >= pages {
+        page.bypass_change_detection().0 = pages - 1;
+    }
+    let page = **page;
+
+    let font = &game_assets.main_font;
+    commands
+        .spawn((
+            Name::new("Records"),
+            RecordsScreen,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 1.0)),
+            StateScoped(GameState::Records),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Review a Game"),
+                TextFont::from_font(font.clone()).with_font_size(60.0),
+                TextColor(Color::WHITE),
+            ));
+            for path in paths.iter().skip(page * PAGE_SIZE).take(PAGE_SIZE) {
+                spawn_record(parent, font, path);
+            }
+            parent.spawn((
+                Text::new(format!(
+                    "Records copied into {} can be reviewed here too.",
+                    records_dir().display()
+                )),
+                TextFont::from_font(font.clone()).with_font_size(20.0),
+                TextColor(Color::Srgba(SILVER)),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Node {
+                    max_width: Val::Percent(60.0),
+                    margin: UiRect::vertical(Val::Px(10.0)),
+                    ..Default::default()
+                },
+            ));
+
+            if page > 0 {
+                spawn_button(
+                    parent,
+                    font,
+                    "Newer",
+                    |_click: Trigger<Pointer<Click>>, mut page: ResMut<RecordPage>| **page -= 1,
+                );
+            }
+            if page + 1 < pages {
+                spawn_button(
+                    parent,
+                    font,
+                    "Older",
+                    |_click: Trigger<Pointer<Click>>, mut page: ResMut<RecordPage>| **page += 1,
+                );
+            }
+            spawn_button(
+                parent,
+                font,
+                "Back",
+                |_click: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<GameState>>| {
+                    next_state.set(GameState::Menu);
+                },
+            );
+        });
+}
+
+/// Spawns a button that replays the record at `path`, or a note saying why it can't be.
+fn spawn_record(parent: &mut ChildBuilder, font: &Handle<Font>, path: &Path) {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let record = match GameRecord::load(path) {
+        Ok(record) => record,
+        Err(e) => {
+            tracing::warn!("{e}");
+            spawn_unplayable(parent, font, format!("{name}: Unreadable"));
+            return;
+        }
+    };
+    // Only records that play through are worth watching.
+    let Some((chosen, _)) = record.replay() else {
+        spawn_unplayable(parent, font, format!("{name}: Doesn't Replay"));
+        return;
+    };
+
+    let label = format!(
+        "{}, {} moves, {}, {}",
+        chosen.name(),
+        record.moves.len(),
+        result_name(record.result),
+        age(record.started_at),
+    );
+    let mut picked = Some((chosen, record.moves));
+    spawn_button(
+        parent,
+        font,
+        &label,
+        move |_click: Trigger<Pointer<Click>>,
+              mut commands: Commands,
+              mut next_state: ResMut<NextState<GameState>>| {
+            let Some((chosen, moves)) = picked.take() else {
+                return;
+            };
+            commands.insert_resource(chosen);
+            commands.insert_resource(Replay::new(moves));
+            next_state.set(GameState::Replay);
+        },
+    );
+}
+
+/// Spawns a record that can't be replayed, in the place of its button.
+fn spawn_unplayable(parent: &mut ChildBuilder, font: &Handle<Font>, label: String) {
+    parent.spawn((
+        Text::new(label),
+        TextFont::from_font(font.clone()).with_font_size(30.0),
+        TextColor(Color::Srgba(SILVER)),
+    ));
+}
+
+fn result_name(result: Option<Outcome>) -> String {
+    match result {
+        Some(Outcome::Winner(winner)) => format!("{} Won", PLAYER_NAMES[*winner]),
+        Some(Outcome::Draw) => "Drawn".to_string(),
+        None => "Unfinished".to_string(),
+    }
+}
+
+/// How long ago a game started at `started_at` was, roughly.
+fn age(started_at: u64) -> String {
+    let minutes = now().saturating_sub(started_at) / 60;
+    let (count, unit) = match minutes {
+        0 => return "just now".to_string(),
+        1..60 => (minutes, "minute"),
+        60..1440 => (minutes / 60, "hour"),
+        _ => (minutes / 1440, "day"),
+    };
+    let plural = if count == 1 { "" } else { "s" };
+    format!("{count} {unit}{plural} ago")
+}
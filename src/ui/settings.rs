@@ -0,0 +1,142 @@
+//! The screen for settings that apply to every game, like how loud it is.
+
+use bevy::{app, prelude::*};
+use bevy_prefs_lite::{Preferences, PreferencesGroup, PreferencesGroupMut, StartAutosaveTimer};
+
+use crate::{
+    assets::GameAssets,
+    audio::SoundSettings,
+    camera::CameraSettings,
+    game::{preview::PreviewSettings, GameState},
+};
+
+use super::{spawn_button, spawn_option};
+
+/// The preferences file the settings are kept in, with each part of the game's in its own group.
+const FILENAME: &str = "settings";
+
+pub struct Plugin;
+
+impl app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Settings), spawn_settings_screen)
+            .add_systems(
+                Update,
+                spawn_settings_screen.run_if(
+                    in_state(GameState::Settings).and(
+                        resource_changed::<SoundSettings>
+                            .or(resource_changed::<CameraSettings>)
+                            .or(resource_changed::<PreviewSettings>),
+                    ),
+                ),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
+struct SettingsScreen;
+
+/// Lays out the screen from the current settings, replacing it if it's already there.
+fn spawn_settings_screen(
+    mut commands: Commands,
+    screens: Query<Entity, With<SettingsScreen>>,
+    game_assets: Res<GameAssets>,
+    sound: Res<SoundSettings>,
+    camera: Res<CameraSettings>,
+    preview: Res<PreviewSettings>,
+) {
+    for screen in &screens {
+        commands.entity(screen).despawn_recursive();
+    }
+
+    let font = &game_assets.main_font;
+    commands
+        .spawn((
+            Name::new("Settings"),
+            SettingsScreen,
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                ..Default::default()
+            },
+            BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 1.0)),
+            StateScoped(GameState::Settings),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Settings"),
+                TextFont::from_font(font.clone()).with_font_size(60.0),
+                TextColor(Color::WHITE),
+            ));
+            spawn_option(
+                parent,
+                font,
+                format!("Master Volume: {}%", sound.master),
+                SoundSettings::next_master,
+            );
+            spawn_option(
+                parent,
+                font,
+                format!("Effects Volume: {}%", sound.effects),
+                SoundSettings::next_effects,
+            );
+            spawn_option(
+                parent,
+                font,
+                format!(
+                    "Follow Current Player: {}",
+                    if camera.follow_player { "On" } else { "Off" }
+                ),
+                CameraSettings::toggle_follow_player,
+            );
+            spawn_option(
+                parent,
+                font,
+                format!(
+                    "Move Preview: {}",
+                    if preview.enabled { "On" } else { "Off" }
+                ),
+                PreviewSettings::toggle_enabled,
+            );
+
+            spawn_button(
+                parent,
+                font,
+                "Back",
+                |_click: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<GameState>>| {
+                    next_state.set(GameState::Menu);
+                },
+            );
+        });
+}
+
+/// Picks up the settings kept in `group` from last time with `load`, if there are any.
+pub fn load_settings(prefs: &Preferences, group: &str, load: impl FnOnce(&PreferencesGroup)) {
+    if let Some(group) = prefs.get(FILENAME).and_then(|file| file.get_group(group)) {
+        load(&group);
+    }
+}
+
+/// Remembers the settings kept in `group` for next time with `store`, warning that `what` can't be
+/// remembered if the preferences can't be opened.
+pub fn store_settings(
+    prefs: &mut Preferences,
+    commands: &mut Commands,
+    group: &str,
+    what: &str,
+    store: impl FnOnce(&mut PreferencesGroupMut),
+) {
+    let Some(mut file) = prefs.get_mut(FILENAME) else {
+        tracing::warn!("Unable to open preferences to remember {what}");
+        return;
+    };
+    let Some(mut group) = file.get_group_mut(group) else {
+        tracing::warn!("Unable to open preferences to remember {what}");
+        return;
+    };
+    store(&mut group);
+    commands.queue(StartAutosaveTimer);
+}